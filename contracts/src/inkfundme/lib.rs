@@ -2,9 +2,9 @@
 
 #[ink::contract]
 mod inkfundme {
-    use ink::env::call::FromAddr;
+    use ink::env::{call::FromAddr, hash::Blake2x256};
     use ink::{
-        U256,
+        H256, U256,
         prelude::string::String,
         prelude::vec::Vec,
         storage::{
@@ -12,7 +12,40 @@ mod inkfundme {
             {Mapping, StorageVec},
         },
     };
-    use token::{Error as TokenError, TokenRef};
+    #[cfg(all(test, feature = "mock-token"))]
+    use mock_token::TokenRef;
+    use token::Error as TokenError;
+    #[cfg(not(all(test, feature = "mock-token")))]
+    use token::TokenRef;
+
+    /// Maximum number of milestones a campaign can be split into
+    const MAX_MILESTONES: usize = 16;
+
+    /// Basis points representing 100%
+    const BASIS_POINTS: u32 = 10_000;
+
+    /// Maximum number of campaigns scanned by a single paginated query
+    const MAX_PAGE_SIZE: u32 = 100;
+
+    /// Maximum number of campaigns returned by `top_campaigns_by_raised`
+    const MAX_TOP_CAMPAIGNS: u32 = 20;
+
+    /// Maximum length in bytes of the prefix `find_campaigns_by_title_prefix`
+    /// searches for
+    const MAX_SEARCH_PREFIX_LEN: usize = 64;
+
+    /// Maximum number of entries kept in a contribution history
+    const MAX_CONTRIBUTION_HISTORY: usize = 32;
+
+    /// Maximum length in bytes of a campaign's goal currency label
+    const MAX_GOAL_CURRENCY_LEN: usize = 12;
+
+    /// Maximum number of campaigns `create_campaigns` creates in one call
+    const MAX_CAMPAIGN_BATCH_LEN: usize = 10;
+
+    /// Version of the contract code, bumped on every release changing its
+    /// interface or storage layout
+    pub const CONTRACT_VERSION: u32 = 1;
 
     /// Campaign structure containing all campaign details
     #[derive(Clone, Debug, PartialEq, Eq)]
@@ -25,21 +58,216 @@ mod inkfundme {
         pub goal: U256,
         pub deadline: u64,
         pub owner: Address,
+        /// Amount the campaign currently holds; withdrawals and refunds reduce it
+        pub raised: U256,
+        pub completed: bool,
+        /// Tranches paid out after success as `(amount, released)`, empty if the
+        /// whole amount is paid out on finalize
+        pub milestones: Vec<(U256, bool)>,
+        /// Token contributions are made in, `None` for the platform's default token
+        pub token: Option<Address>,
+        /// Minimum amount the campaign needs to raise to succeed; `goal` is the
+        /// target shown to contributors
+        pub soft_cap: U256,
+        /// Block timestamp at which the campaign was created
+        pub created_at: u64,
+        /// Whether the campaign owner may contribute to their own campaign
+        pub allow_self_contribution: bool,
+        /// Informational label of the currency the goal is expressed in, such as
+        /// "€" or "IFM"; empty if not set
+        pub goal_currency: String,
+        /// Whether the admin made the succeeded campaign refundable with
+        /// `enable_refunds`, which then counts as failed
+        pub refunds_forced: bool,
+    }
+
+    /// Optional settings for `create_campaign`
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub struct CampaignOptions {
+        /// Tranche amounts released one by one after success, which must sum to
+        /// the goal; empty to pay out everything on finalize
+        pub milestones: Vec<U256>,
+        /// Token contributions are made in, `None` for the platform's default token
+        pub token: Option<Address>,
+        /// Minimum amount to raise for the campaign to succeed, at most the goal;
+        /// `0` to require the full goal
+        pub soft_cap: U256,
+        /// Whether the campaign owner may contribute to their own campaign,
+        /// `false` to keep owners from faking traction
+        pub allow_self_contribution: bool,
+        /// Label of the currency the goal is expressed in, at most
+        /// `MAX_GOAL_CURRENCY_LEN` bytes; empty for none
+        pub goal_currency: String,
+    }
+
+    impl Default for CampaignOptions {
+        fn default() -> Self {
+            Self {
+                milestones: Vec::new(),
+                token: None,
+                soft_cap: U256::zero(),
+                allow_self_contribution: true,
+                goal_currency: String::new(),
+            }
+        }
+    }
+
+    /// Compact view of a campaign without its title and description, for list
+    /// views
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub struct CampaignSummary {
+        pub id: u32,
+        pub goal: U256,
         pub raised: U256,
+        pub deadline: u64,
         pub completed: bool,
     }
 
+    impl From<&Campaign> for CampaignSummary {
+        fn from(campaign: &Campaign) -> Self {
+            Self {
+                id: campaign.id,
+                goal: campaign.goal,
+                raised: campaign.raised,
+                deadline: campaign.deadline,
+                completed: campaign.completed,
+            }
+        }
+    }
+
+    /// Lifecycle state of a campaign as shown to contributors
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum CampaignStatus {
+        /// Accepting contributions
+        Active,
+        /// Past its deadline but not finalized yet
+        Expired,
+        /// Finalized after raising enough
+        Succeeded,
+        /// Finalized without raising enough, contributions are refundable
+        Failed,
+    }
+
+    impl CampaignStatus {
+        /// Returns a stable numeric code for the status, for clients that can't
+        /// decode the enum
+        ///
+        /// `0` Active, `1` Expired (ended but not finalized), `2` Succeeded, `3`
+        /// Failed
+        pub fn code(&self) -> u8 {
+            match self {
+                CampaignStatus::Active => 0,
+                CampaignStatus::Expired => 1,
+                CampaignStatus::Succeeded => 2,
+                CampaignStatus::Failed => 3,
+            }
+        }
+    }
+
+    /// Platform settings fixed at construction
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(StorageLayout))]
+    pub struct Config {
+        /// Time after a campaign deadline during which contributions are still
+        /// accepted and finalization is blocked
+        pub grace_period: u64,
+        /// Time an account has to wait between two `mint_faucet` calls
+        pub faucet_cooldown: u64,
+        /// Maximum amount minted by a single `mint_faucet` call, `0` for no cap
+        pub faucet_max: U256,
+        /// Maximum time between campaign creation and its deadline, `0` for no
+        /// limit
+        pub max_duration: u64,
+        /// Minimum time between campaign creation and its deadline, `0` for no
+        /// minimum
+        pub min_duration: u64,
+        /// Time after a failed campaign's contribution period during which
+        /// refunds are reserved for contributors; once it passes, the admin can
+        /// sweep unclaimed refunds. `0` disables sweeping
+        pub refund_window: u64,
+        /// Whether a campaign's details become immutable once it has raised
+        /// anything
+        pub lock_on_first_contribution: bool,
+        /// Smallest goal a campaign can be created with, `0` for no minimum
+        pub min_goal: U256,
+        /// Flat fee in the default token charged for creating a campaign, `0`
+        /// for none
+        pub creation_fee: U256,
+        /// Account receiving the creation fees
+        pub fee_recipient: Address,
+    }
+
     /// Main InkFundMe contract storage
     #[ink(storage)]
     pub struct InkFundMe {
         /// Address of the deployed ERC20 token contract
         token_contract: TokenRef,
-        /// Vector storing all campaigns
+        /// Address of `token_contract`, kept to answer without a cross-contract call
+        token_address: Address,
+        /// Vector storing all campaigns, indexed by ID. Removed campaigns leave a
+        /// cleared cell behind
         campaigns: StorageVec<Campaign>,
         /// Mapping to track contributions: (campaign_id, contributor) -> amount
         contributions: Mapping<(u32, Address), U256>,
         /// Counter for generating unique campaign IDs
         next_campaign_id: u32,
+        /// Platform settings
+        config: Config,
+        /// Token `(name, symbol, decimals)` cached at construction by `new_checked`
+        /// or `new_with_token`
+        token_metadata: Option<(String, String, u8)>,
+        /// Total tokens held on behalf of all campaigns
+        total_raised_all: U256,
+        /// Account allowed to manage platform-wide settings, `None` once the
+        /// admin role was renounced
+        admin: Option<Address>,
+        /// Account the admin offered the role to, until it accepts
+        pending_admin: Option<Address>,
+        /// Tracks overfunding refunds: (campaign_id, contributor) -> claimed
+        overfunding_claimed: Mapping<(u32, Address), bool>,
+        /// Total overfunding refunded per campaign, already deducted from its
+        /// `raised`
+        overfunding_paid: Mapping<u32, U256>,
+        /// Timestamp of each account's last faucet mint
+        last_faucet: Mapping<Address, u64>,
+        /// Total refunded per campaign, including contributions withdrawn before
+        /// the deadline
+        total_refunded: Mapping<u32, U256>,
+        /// Failed campaigns whose unclaimed refunds were swept by the admin
+        swept: Mapping<u32, bool>,
+        /// When `enable_refunds` unwound each campaign, starting its refund window
+        refunds_enabled_at: Mapping<u32, u64>,
+        /// Whether campaign creation and contributions are halted
+        paused: bool,
+        /// Campaigns whose raised amount has reached their goal at least once
+        goal_reached: Mapping<u32, bool>,
+        /// IDs of the campaigns each contributor was credited for, in order of
+        /// their first contribution: (contributor, index) -> campaign_id
+        contributed_campaigns: Mapping<(Address, u32), u32>,
+        /// Number of entries each contributor has in `contributed_campaigns`
+        contributed_campaign_counts: Mapping<Address, u32>,
+        /// Whether a contributor has an entry in `contributed_campaigns` for a
+        /// campaign: (campaign_id, contributor) -> listed
+        contributed_to: Mapping<(u32, Address), bool>,
+        /// Proceeds of successful campaigns not yet claimed by their owners
+        proceeds: Mapping<u32, U256>,
+        /// `CONTRACT_VERSION` the storage was laid out with, for migrations to
+        /// branch on
+        schema_version: u32,
+        /// Number of campaigns not finalized yet
+        active_campaigns: u32,
+        /// Number of finalized campaigns
+        completed_campaigns: u32,
+        /// Individual contributions as `(timestamp, amount)`:
+        /// (campaign_id, contributor) -> the last `MAX_CONTRIBUTION_HISTORY`
+        contribution_history: Mapping<(u32, Address), Vec<(u64, U256)>>,
+        /// Number of contributors currently holding a contribution to each
+        /// campaign
+        contributor_counts: Mapping<u32, u32>,
     }
 
     /// Events emitted by the contract
@@ -49,6 +277,9 @@ mod inkfundme {
         id: u32,
         #[ink(topic)]
         owner: Address,
+        /// Blake2x256 hash of the campaign title
+        #[ink(topic)]
+        title_hash: [u8; 32],
         goal: U256,
         deadline: u64,
     }
@@ -57,16 +288,64 @@ mod inkfundme {
     pub struct ContributionMade {
         #[ink(topic)]
         campaign_id: u32,
+        /// Address credited with the contribution
         #[ink(topic)]
         contributor: Address,
+        /// Address the tokens were pulled from
+        #[ink(topic)]
+        payer: Address,
         amount: U256,
     }
 
+    /// Emitted once, by the contribution that first brings a campaign to its goal
+    #[ink(event)]
+    pub struct GoalReached {
+        #[ink(topic)]
+        campaign_id: u32,
+        raised: U256,
+    }
+
+    #[ink(event)]
+    pub struct DeadlineExtended {
+        #[ink(topic)]
+        campaign_id: u32,
+        deadline: u64,
+    }
+
+    #[ink(event)]
+    pub struct CampaignMetadataUpdated {
+        #[ink(topic)]
+        campaign_id: u32,
+    }
+
     #[ink(event)]
     pub struct CampaignFinalized {
         #[ink(topic)]
         campaign_id: u32,
         success: bool,
+        /// Amount released to the owner on finalization, claimable with
+        /// `claim_proceeds`
+        amount_released: U256,
+        /// Receiver of the payout, the zero address if the campaign failed
+        #[ink(topic)]
+        recipient: Address,
+    }
+
+    #[ink(event)]
+    pub struct ProceedsClaimed {
+        #[ink(topic)]
+        campaign_id: u32,
+        #[ink(topic)]
+        owner: Address,
+        amount: U256,
+    }
+
+    #[ink(event)]
+    pub struct MilestoneReleased {
+        #[ink(topic)]
+        campaign_id: u32,
+        index: u32,
+        amount: U256,
     }
 
     #[ink(event)]
@@ -78,6 +357,106 @@ mod inkfundme {
         amount: U256,
     }
 
+    /// Emitted when a contributor takes back (part of) their contribution while
+    /// the campaign is still running, unlike `RefundClaimed` after a failure
+    #[ink(event)]
+    pub struct ContributionWithdrawn {
+        #[ink(topic)]
+        campaign_id: u32,
+        #[ink(topic)]
+        contributor: Address,
+        amount: U256,
+        /// Contribution left after the withdrawal
+        remaining: U256,
+    }
+
+    #[ink(event)]
+    pub struct OverfundingRefundClaimed {
+        #[ink(topic)]
+        campaign_id: u32,
+        #[ink(topic)]
+        contributor: Address,
+        amount: U256,
+    }
+
+    #[ink(event)]
+    pub struct TokenContractUpdated {
+        #[ink(topic)]
+        token_address: Address,
+    }
+
+    /// Emitted when the admin makes a succeeded campaign refundable
+    #[ink(event)]
+    pub struct RefundsEnabled {
+        #[ink(topic)]
+        campaign_id: u32,
+    }
+
+    #[ink(event)]
+    pub struct CampaignRemoved {
+        #[ink(topic)]
+        campaign_id: u32,
+        #[ink(topic)]
+        by: Address,
+    }
+
+    #[ink(event)]
+    pub struct UnclaimedRefundsSwept {
+        #[ink(topic)]
+        campaign_id: u32,
+        #[ink(topic)]
+        to: Address,
+        amount: U256,
+    }
+
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        by: Address,
+    }
+
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        by: Address,
+    }
+
+    #[ink(event)]
+    pub struct OwnershipTransferStarted {
+        #[ink(topic)]
+        previous_admin: Address,
+        #[ink(topic)]
+        new_admin: Address,
+    }
+
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        previous_admin: Option<Address>,
+        #[ink(topic)]
+        new_admin: Address,
+    }
+
+    #[ink(event)]
+    pub struct OwnershipRenounced {
+        #[ink(topic)]
+        previous_admin: Address,
+    }
+
+    #[ink(event)]
+    pub struct StuckTokensWithdrawn {
+        #[ink(topic)]
+        to: Address,
+        amount: U256,
+    }
+
+    #[ink(event)]
+    pub struct NativeWithdrawn {
+        #[ink(topic)]
+        to: Address,
+        amount: U256,
+    }
+
     /// InkFundMe contract errors
     #[derive(Debug, PartialEq, Eq)]
     #[ink::scale_derive(Encode, Decode, TypeInfo)]
@@ -100,11 +479,90 @@ mod inkfundme {
         TokenError(TokenError),
         /// Invalid campaign parameters
         InvalidParameters,
+        /// Campaign has not been finalized yet
+        NotFinalized,
+        /// Milestone was already released
+        MilestoneAlreadyReleased,
+        /// Caller is not allowed to perform this action
+        NotAuthorized,
+        /// Campaigns still hold funds in the current token
+        ActiveCampaignsExist,
+        /// Refund was already claimed
+        AlreadyClaimed,
+        /// Faucet was used too recently by the caller
+        FaucetCooldown,
+        /// Caller has no balance or allowance available to contribute
+        NothingToContribute,
+        /// Amount exceeds the tokens not owed to any campaign
+        InsufficientExcess,
+        /// Arithmetic operation overflowed
+        Overflow,
+        /// Contract is paused
+        Paused,
+        /// Campaign can no longer be edited because it has received contributions
+        CampaignLocked,
+        /// Campaign was already finalized
+        AlreadyFinalized,
+        /// Contributor hasn't approved this contract to spend enough tokens
+        InsufficientAllowance,
+        /// Contributor doesn't hold enough tokens
+        InsufficientTokenBalance,
+        /// Less of the contribution would be accepted than the contributor allowed
+        SlippageExceeded,
+        /// Campaign owner tried to contribute to a campaign that forbids it
+        SelfContribution,
+        /// Campaign still holds funds
+        CampaignNotEmpty,
+        /// Sending native currency out of the contract failed
+        NativeTransferFailed,
     }
 
     /// Result type for contract operations
     pub type Result<T> = core::result::Result<T, Error>;
 
+    /// Offset added to token error codes wrapped in `Error::TokenError`
+    pub const TOKEN_ERROR_CODE_OFFSET: u32 = 1000;
+
+    impl Error {
+        /// Returns a stable numeric code for the error, for clients that can't
+        /// decode the enum
+        ///
+        /// Codes start at `1` and follow declaration order; they never change once
+        /// assigned. Token errors map to `TOKEN_ERROR_CODE_OFFSET` plus the
+        /// token's own code
+        pub fn code(&self) -> u32 {
+            match self {
+                Error::CampaignNotFound => 1,
+                Error::DeadlineNotReached => 2,
+                Error::DeadlineReached => 3,
+                Error::CampaignCompleted => 4,
+                Error::GoalNotMet => 5,
+                Error::NoContribution => 6,
+                Error::OnlyOwner => 7,
+                Error::TokenError(error) => TOKEN_ERROR_CODE_OFFSET + error.code(),
+                Error::InvalidParameters => 8,
+                Error::NotFinalized => 9,
+                Error::MilestoneAlreadyReleased => 10,
+                Error::NotAuthorized => 11,
+                Error::ActiveCampaignsExist => 12,
+                Error::AlreadyClaimed => 13,
+                Error::FaucetCooldown => 14,
+                Error::NothingToContribute => 15,
+                Error::InsufficientExcess => 16,
+                Error::Overflow => 17,
+                Error::Paused => 18,
+                Error::CampaignLocked => 19,
+                Error::AlreadyFinalized => 20,
+                Error::InsufficientAllowance => 21,
+                Error::InsufficientTokenBalance => 22,
+                Error::SlippageExceeded => 23,
+                Error::SelfContribution => 24,
+                Error::CampaignNotEmpty => 25,
+                Error::NativeTransferFailed => 26,
+            }
+        }
+    }
+
     impl From<TokenError> for Error {
         fn from(error: TokenError) -> Self {
             Error::TokenError(error)
@@ -116,25 +574,128 @@ mod inkfundme {
         ///
         /// # Parameters
         /// - `token_address`: Address of an existing ERC20 token contract
+        /// - `config`: Platform settings
         ///
         /// # Returns
         /// New InkFundMe contract instance using the provided token
         #[ink(constructor)]
-        pub fn new(token_address: Address) -> Self {
+        pub fn new(token_address: Address, config: Config) -> Self {
             let token_contract = TokenRef::from_addr(token_address);
 
             Self {
                 token_contract,
+                token_address,
                 campaigns: StorageVec::new(),
                 contributions: Mapping::new(),
                 next_campaign_id: 0,
+                config,
+                token_metadata: None,
+                total_raised_all: U256::zero(),
+                admin: Some(Self::env().caller()),
+                pending_admin: None,
+                overfunding_claimed: Mapping::new(),
+                overfunding_paid: Mapping::new(),
+                last_faucet: Mapping::new(),
+                total_refunded: Mapping::new(),
+                swept: Mapping::new(),
+                refunds_enabled_at: Mapping::new(),
+                paused: false,
+                goal_reached: Mapping::new(),
+                contributed_campaigns: Mapping::new(),
+                contributed_campaign_counts: Mapping::new(),
+                contributed_to: Mapping::new(),
+                proceeds: Mapping::new(),
+                schema_version: CONTRACT_VERSION,
+                active_campaigns: 0,
+                completed_campaigns: 0,
+                contribution_history: Mapping::new(),
+                contributor_counts: Mapping::new(),
+            }
+        }
+
+        /// Constructor that deploys a fresh token alongside the platform
+        ///
+        /// Contracts can only instantiate code that is already on chain, so the
+        /// token has to be uploaded first and is referenced by `token_code_hash`.
+        /// This also lets the deployer pick the token version to use
+        ///
+        /// The token's initial supply is forwarded to the deployer, and ownership
+        /// of the token is offered to them: it passes once they call the token's
+        /// `accept_ownership`, until then this contract stays the owner
+        ///
+        /// # Parameters
+        /// - `token_code_hash`: Code hash of the uploaded token contract
+        /// - `name`: Token name, truncated to the token's `MAX_NAME_LEN` bytes
+        /// - `symbol`: Token symbol, truncated to the token's `MAX_SYMBOL_LEN`
+        ///   bytes
+        /// - `decimals`: Number of token decimals, clamped to the token's
+        ///   `MAX_DECIMALS`
+        /// - `initial_supply`: Initial token supply minted to the deployer
+        /// - `config`: Platform settings
+        ///
+        /// # Returns
+        /// New InkFundMe contract instance using the new token, or the token's
+        /// error if handing over the supply or ownership fails
+        #[ink(constructor)]
+        pub fn new_with_token(
+            token_code_hash: H256,
+            name: String,
+            symbol: String,
+            decimals: u8,
+            initial_supply: U256,
+            config: Config,
+        ) -> Result<Self> {
+            // Cross-contract instantiation; the supply is minted to this contract
+            let token_contract = TokenRef::new(name, symbol, decimals, initial_supply, None)
+                .code_hash(token_code_hash)
+                .endowment(0.into())
+                .salt_bytes(None)
+                .instantiate();
+            let token_address = ink::ToAddr::to_addr(&token_contract);
+
+            let mut contract = Self::new(token_address, config);
+            // The token truncates and clamps its metadata, cache what it stores
+            contract.token_metadata = Some(contract.get_token_metadata());
+
+            let deployer = Self::env().caller();
+            if initial_supply > U256::zero() {
+                contract.token_contract.transfer(deployer, initial_supply)?;
             }
+            contract.token_contract.transfer_ownership(deployer)?;
+
+            Ok(contract)
+        }
+
+        /// Constructor that validates the token before accepting it
+        ///
+        /// Queries the token's metadata and caches it, so instantiation fails early
+        /// if `token_address` doesn't point to an ERC20 token contract
+        ///
+        /// # Parameters
+        /// - `token_address`: Address of an existing ERC20 token contract
+        /// - `config`: Platform settings
+        ///
+        /// # Returns
+        /// New InkFundMe contract instance using the provided token
+        #[ink(constructor)]
+        pub fn new_checked(token_address: Address, config: Config) -> Self {
+            let mut contract = Self::new(token_address, config);
+
+            // Cross-contract calls to read the token metadata
+            let name = contract.token_contract.name();
+            let symbol = contract.token_contract.symbol();
+            let decimals = contract.token_contract.decimals();
+            contract.token_metadata = Some((name, symbol, decimals));
+
+            contract
         }
 
         /// Mint tokens for free (faucet functionality for testing)
         ///
+        /// Each account can mint at most `faucet_max` tokens per call and has to
+        /// wait `faucet_cooldown` between calls
+        ///
         /// # Parameters
-        /// - `to`: Address to mint tokens to
         /// - `amount`: Amount of tokens to mint
         ///
         /// # Returns
@@ -142,6 +703,18 @@ mod inkfundme {
         #[ink(message)]
         pub fn mint_faucet(&mut self, amount: U256) -> Result<()> {
             let to = self.env().caller();
+            let now = self.env().block_timestamp();
+
+            if self.config.faucet_max != U256::zero() && amount > self.config.faucet_max {
+                return Err(Error::InvalidParameters);
+            }
+            if let Some(last) = self.last_faucet.get(to) {
+                if now < last.saturating_add(self.config.faucet_cooldown) {
+                    return Err(Error::FaucetCooldown);
+                }
+            }
+            self.last_faucet.insert(to, &now);
+
             // Cross-contract call to mint tokens
             Ok(self.token_contract.mint(to, amount)?)
         }
@@ -153,9 +726,11 @@ mod inkfundme {
         /// - `description`: Campaign description
         /// - `goal`: Fundraising goal in tokens
         /// - `deadline`: Campaign deadline (timestamp)
+        /// - `options`: Milestones, contribution token and soft cap, see
+        ///   `CampaignOptions`
         ///
         /// # Returns
-        /// Campaign ID of the newly created campaign
+        /// The newly created campaign, including its assigned ID
         #[ink(message)]
         pub fn create_campaign(
             &mut self,
@@ -163,15 +738,46 @@ mod inkfundme {
             description: String,
             goal: U256,
             deadline: u64,
-        ) -> Result<u32> {
+            options: CampaignOptions,
+        ) -> Result<Campaign> {
+            self.ensure_not_paused()?;
+
             // Validate parameters
-            if goal == U256::zero() || deadline <= self.env().block_timestamp() {
+            self.ensure_valid_goal_and_deadline(goal, deadline)?;
+            let now = self.env().block_timestamp();
+            let soft_cap = if options.soft_cap == U256::zero() {
+                goal
+            } else {
+                options.soft_cap
+            };
+            if soft_cap > goal {
+                return Err(Error::InvalidParameters);
+            }
+            // Milestones pay out the full goal, so they require reaching it
+            if soft_cap != goal && !options.milestones.is_empty() {
+                return Err(Error::InvalidParameters);
+            }
+            if options.goal_currency.len() > MAX_GOAL_CURRENCY_LEN {
                 return Err(Error::InvalidParameters);
             }
+            let milestones = Self::build_milestones(goal, options.milestones)?;
 
             let campaign_id = self.next_campaign_id;
+            let next_campaign_id = campaign_id.checked_add(1).ok_or(Error::Overflow)?;
             let owner = self.env().caller();
 
+            // Collect the creation fee in the default token before recording the
+            // campaign, so a failed transfer leaves no trace
+            if self.config.creation_fee > U256::zero() {
+                self.pull_tokens(
+                    &mut TokenRef::from_addr(self.token_address),
+                    owner,
+                    self.config.fee_recipient,
+                    self.config.creation_fee,
+                )?;
+            }
+            let title_hash = self.env().hash_bytes::<Blake2x256>(title.as_bytes());
+
             let campaign = Campaign {
                 id: campaign_id,
                 title,
@@ -181,421 +787,7718 @@ mod inkfundme {
                 owner,
                 raised: U256::zero(),
                 completed: false,
+                milestones,
+                token: options.token,
+                soft_cap,
+                created_at: now,
+                allow_self_contribution: options.allow_self_contribution,
+                goal_currency: options.goal_currency,
+                refunds_forced: false,
             };
 
             self.campaigns.push(&campaign);
-            self.next_campaign_id += 1;
+            self.next_campaign_id = next_campaign_id;
+            self.active_campaigns = self.active_campaigns.saturating_add(1);
 
             // Emit event
             self.env().emit_event(CampaignCreated {
                 id: campaign_id,
                 owner,
+                title_hash,
                 goal,
                 deadline,
             });
 
-            Ok(campaign_id)
+            Ok(campaign)
         }
 
-        /// Contribute tokens to a campaign
+        /// Create a new fundraising campaign with amounts given in whole tokens
+        ///
+        /// Amounts are scaled by `10^decimals` of the default token, whose
+        /// metadata is fetched once and cached. The campaign uses the default token
         ///
         /// # Parameters
-        /// - `campaign_id`: ID of the campaign to contribute to
-        /// - `amount`: Amount of tokens to contribute
+        /// - `title`: Campaign title
+        /// - `description`: Campaign description
+        /// - `goal`: Fundraising goal in whole tokens
+        /// - `deadline`: Campaign deadline (timestamp)
+        /// - `milestones`: Tranche amounts in whole tokens, see `CampaignOptions`
         ///
         /// # Returns
-        /// Result indicating success or failure
+        /// The newly created campaign, including its assigned ID
         #[ink(message)]
-        pub fn contribute(&mut self, campaign_id: u32, amount: U256) -> Result<()> {
-            // Get campaign (this will fail if campaign doesn't exist)
-            let mut campaign = self.get_campaign_mut(campaign_id)?;
-
-            // Check if deadline has passed
-            if self.env().block_timestamp() > campaign.deadline {
-                return Err(Error::DeadlineReached);
-            }
-
-            // Check if campaign is already completed
-            if campaign.completed {
-                return Err(Error::CampaignCompleted);
-            }
-
-            let contributor = self.env().caller();
-            let contract_address = self.env().address();
-
-            // Transfer tokens from contributor to this contract
-            self.token_contract
-                .transfer_from(contributor, contract_address, amount)?;
-
-            // Update campaign raised amount
-            campaign.raised = campaign.raised.checked_add(amount).unwrap();
-
-            // Update contributor's contribution
-            let current_contribution = self
-                .contributions
-                .get((campaign_id, contributor))
-                .unwrap_or_default();
-            let new_contribution = current_contribution.checked_add(amount).unwrap();
-            self.contributions
-                .insert((campaign_id, contributor), &new_contribution);
-
-            // Update the campaign in storage
-            self.campaigns.set(campaign_id, &campaign);
-
-            // Emit event
-            self.env().emit_event(ContributionMade {
-                campaign_id,
-                contributor,
-                amount,
-            });
+        pub fn create_campaign_whole_tokens(
+            &mut self,
+            title: String,
+            description: String,
+            goal: U256,
+            deadline: u64,
+            milestones: Vec<U256>,
+        ) -> Result<Campaign> {
+            let decimals = self.token_decimals();
+            let goal = Self::scale_whole_tokens(goal, decimals)?;
+            let milestones = milestones
+                .into_iter()
+                .map(|amount| Self::scale_whole_tokens(amount, decimals))
+                .collect::<Result<Vec<_>>>()?;
 
-            Ok(())
+            let options = CampaignOptions {
+                milestones,
+                ..CampaignOptions::default()
+            };
+            self.create_campaign(title, description, goal, deadline, options)
         }
 
-        /// Finalize a campaign (transfer funds to owner or mark as failed)
+        /// Create several campaigns at once, all or none
+        ///
+        /// Every spec is validated before any campaign is created. The campaigns
+        /// use the default options, and a creation fee is charged for each
         ///
         /// # Parameters
-        /// - `campaign_id`: ID of the campaign to finalize
+        /// - `specs`: `(title, description, goal, deadline)` of each campaign, at
+        ///   most `MAX_CAMPAIGN_BATCH_LEN`
         ///
         /// # Returns
-        /// Result indicating success or failure
+        /// The IDs assigned to the campaigns, in spec order
         #[ink(message)]
-        pub fn finalize(&mut self, campaign_id: u32) -> Result<()> {
-            let mut campaign = self.get_campaign_mut(campaign_id)?;
+        pub fn create_campaigns(
+            &mut self,
+            specs: Vec<(String, String, U256, u64)>,
+        ) -> Result<Vec<u32>> {
+            self.ensure_not_paused()?;
 
-            // Only the campaign owner can finalize
-            let caller = self.env().caller();
-            if caller != campaign.owner {
-                return Err(Error::OnlyOwner);
+            if specs.is_empty() || specs.len() > MAX_CAMPAIGN_BATCH_LEN {
+                return Err(Error::InvalidParameters);
             }
-
-            // Check if deadline has passed
-            if self.env().block_timestamp() <= campaign.deadline {
-                return Err(Error::DeadlineNotReached);
+            for (_, _, goal, deadline) in &specs {
+                self.ensure_valid_goal_and_deadline(*goal, *deadline)?;
             }
 
-            // Check if campaign is already completed
-            if campaign.completed {
-                return Err(Error::CampaignCompleted);
+            specs
+                .into_iter()
+                .map(|(title, description, goal, deadline)| {
+                    self.create_campaign(
+                        title,
+                        description,
+                        goal,
+                        deadline,
+                        CampaignOptions::default(),
+                    )
+                    .map(|campaign| campaign.id)
+                })
+                .collect()
+        }
+
+        /// Contribute tokens to a campaign
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign to contribute to
+        /// - `amount`: Amount of tokens to contribute
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn contribute(&mut self, campaign_id: u32, amount: U256) -> Result<()> {
+            let contributor = self.env().caller();
+            self.contribute_for(campaign_id, contributor, amount)
+        }
+
+        /// Contribute tokens to a campaign, reporting failures as error codes
+        ///
+        /// Same as `contribute`, for clients that can't decode the error enum
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign to contribute to
+        /// - `amount`: Amount of tokens to contribute
+        ///
+        /// # Returns
+        /// `0` on success, otherwise the `Error::code` of the failure
+        #[ink(message)]
+        pub fn try_contribute_code(&mut self, campaign_id: u32, amount: U256) -> u32 {
+            match self.contribute(campaign_id, amount) {
+                Ok(()) => 0,
+                Err(error) => error.code(),
             }
+        }
 
-            let success = campaign.raised >= campaign.goal;
-            campaign.completed = true;
+        /// Contribute tokens to a campaign on behalf of another address
+        ///
+        /// Tokens are pulled from the caller, but the contribution is credited to
+        /// `beneficiary`, who is then the one able to claim a refund
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign to contribute to
+        /// - `beneficiary`: Address credited with the contribution
+        /// - `amount`: Amount of tokens to contribute
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn contribute_for(
+            &mut self,
+            campaign_id: u32,
+            beneficiary: Address,
+            amount: U256,
+        ) -> Result<()> {
+            self.ensure_not_paused()?;
+            if amount == U256::zero() {
+                return Err(Error::InvalidParameters);
+            }
 
-            if success {
-                // Transfer raised funds to campaign owner
-                self.token_contract
-                    .transfer(campaign.owner, campaign.raised)?;
+            // Get campaign (this will fail if campaign doesn't exist)
+            let campaign = self.get_campaign_mut(campaign_id)?;
+
+            // Check if the campaign still accepts contributions
+            self.ensure_accepting_contributions(&campaign)?;
+
+            let payer = self.env().caller();
+            Self::ensure_not_self_contribution(&campaign, payer)?;
+            Self::ensure_not_self_contribution(&campaign, beneficiary)?;
+            let contract_address = self.env().address();
+
+            // Transfer tokens from payer to this contract. Nothing is written
+            // before this external call, so a failed transfer leaves no trace
+            self.pull_tokens(
+                &mut self.token_of(&campaign),
+                payer,
+                contract_address,
+                amount,
+            )?;
+
+            // Reload the campaign instead of reusing the copy read before the
+            // external call, which a callback could have made stale, then apply
+            // all changes at once
+            let campaign = self.get_campaign_mut(campaign_id)?;
+            self.ensure_accepting_contributions(&campaign)?;
+            self.record_contribution(campaign, payer, beneficiary, amount)
+        }
+
+        /// Contribute tokens sent with the default token's `transfer_and_call`
+        ///
+        /// For callers, typically contracts, that hold the token and would rather
+        /// transfer than `approve` first. Only the default token contract can call
+        /// this, right after moving `value` tokens from `from` to this contract, so
+        /// the contribution is always credited to the account that paid. `data`
+        /// must be the SCALE encoded `u32` ID of the campaign to contribute to,
+        /// which must be funded in the default token
+        ///
+        /// The token expects no return value, so a rejected contribution traps,
+        /// which makes `transfer_and_call` fail and revert the transfer
+        ///
+        /// # Parameters
+        /// - `from`: Account the tokens were transferred from
+        /// - `value`: Amount of tokens transferred
+        /// - `data`: Encoded campaign ID
+        #[ink(message)]
+        pub fn on_token_transfer(&mut self, from: Address, value: U256, data: Vec<u8>) {
+            if let Err(error) = self.credit_token_transfer(from, value, &data) {
+                panic!("contribution rejected: {:?}", error);
             }
-            // If not successful, funds remain in contract for refunds
+        }
 
-            // Update the campaign in storage
-            self.campaigns.set(campaign_id, &campaign);
+        /// Contribute tokens to a campaign using a signed token permit instead of a
+        /// prior `approve` transaction
+        ///
+        /// The permit must be signed by the caller and allow this contract to spend
+        /// `amount` of their tokens (see the token's `permit_hash`), so approval and
+        /// contribution happen in a single call
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign to contribute to
+        /// - `amount`: Amount of tokens to contribute
+        /// - `deadline`: Deadline the permit was signed with
+        /// - `signature`: Caller's signature over the token permit
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn contribute_with_permit(
+            &mut self,
+            campaign_id: u32,
+            amount: U256,
+            deadline: u64,
+            signature: [u8; 65],
+        ) -> Result<()> {
+            self.ensure_not_paused()?;
+            if amount == U256::zero() {
+                return Err(Error::InvalidParameters);
+            }
 
-            // Emit event
-            self.env().emit_event(CampaignFinalized {
-                campaign_id,
-                success,
-            });
+            // Check the campaign before spending the permit
+            let campaign = self.get_campaign_mut(campaign_id)?;
+            self.ensure_accepting_contributions(&campaign)?;
 
-            Ok(())
+            let contributor = self.env().caller();
+            let contract_address = self.env().address();
+
+            // Cross-contract call to approve this contract from the signed permit
+            self.token_of(&campaign).permit(
+                contributor,
+                contract_address,
+                amount,
+                deadline,
+                signature,
+            )?;
+
+            self.contribute_for(campaign_id, contributor, amount)
         }
 
-        /// Claim refund for a failed campaign
+        /// Contribute to a campaign with the chain's native currency
+        ///
+        /// The same amount of tokens is minted to this contract (1 native unit = 1
+        /// token base unit), so the contribution is accounted for, paid out on
+        /// finalize and refunded in tokens like any other. The native value itself
+        /// backs nothing: it stays in this contract until the admin takes it out
+        /// with `withdraw_native`
+        ///
+        /// Relies on the default token's `mint`, so native contributions fail with
+        /// `TokenError(NotAuthorized)` once the token's ownership was renounced
         ///
         /// # Parameters
-        /// - `campaign_id`: ID of the failed campaign
+        /// - `campaign_id`: ID of the campaign to contribute to
         ///
         /// # Returns
         /// Result indicating success or failure
+        #[ink(message, payable)]
+        pub fn contribute_native(&mut self, campaign_id: u32) -> Result<()> {
+            self.ensure_not_paused()?;
+            let amount = self.env().transferred_value();
+            if amount == U256::zero() {
+                return Err(Error::InvalidParameters);
+            }
+
+            let campaign = self.get_campaign_mut(campaign_id)?;
+            self.ensure_accepting_contributions(&campaign)?;
+
+            // Only the default token is minted for native contributions
+            if campaign.token.is_some() {
+                return Err(Error::InvalidParameters);
+            }
+
+            let contributor = self.env().caller();
+            Self::ensure_not_self_contribution(&campaign, contributor)?;
+            let contract_address = self.env().address();
+
+            // Cross-contract call to back the contribution with tokens
+            self.token_contract.mint(contract_address, amount)?;
+
+            // Reload the campaign after the external call, see `contribute_for`
+            let campaign = self.get_campaign_mut(campaign_id)?;
+            self.ensure_accepting_contributions(&campaign)?;
+            self.record_contribution(campaign, contributor, contributor, amount)
+        }
+
+        /// Contribute as much as possible to a campaign
+        ///
+        /// Pledges the lesser of the caller's token balance and the allowance they
+        /// granted this contract, so nothing is left behind as dust
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign to contribute to
+        ///
+        /// # Returns
+        /// Amount of tokens actually contributed
         #[ink(message)]
-        pub fn claim_refund(&mut self, campaign_id: u32) -> Result<()> {
-            let campaign = self.get_campaign(campaign_id)?;
+        pub fn contribute_max(&mut self, campaign_id: u32) -> Result<U256> {
+            self.ensure_not_paused()?;
+
+            // Check the campaign before querying the token
+            let campaign = self.get_campaign_mut(campaign_id)?;
+            self.ensure_accepting_contributions(&campaign)?;
+
             let contributor = self.env().caller();
+            let contract_address = self.env().address();
 
-            // Check if campaign is completed and failed
-            if !campaign.completed {
-                return Err(Error::CampaignCompleted);
+            // Cross-contract calls to read what the caller can spend
+            let token = self.token_of(&campaign);
+            let balance = token.balance_of(contributor);
+            let allowance = token.allowance(contributor, contract_address);
+            let amount = balance.min(allowance);
+            if amount == U256::zero() {
+                return Err(Error::NothingToContribute);
             }
 
-            if campaign.raised >= campaign.goal {
-                return Err(Error::GoalNotMet);
+            self.contribute_for(campaign_id, contributor, amount)?;
+
+            Ok(amount)
+        }
+
+        /// Contribute to a campaign without overshooting its goal
+        ///
+        /// The contribution is clamped to what is still missing to reach the goal,
+        /// so contributors racing to fill a campaign don't overfund it
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign to contribute to
+        /// - `amount`: Maximum amount of tokens to contribute
+        /// - `min_accepted`: Smallest accepted amount the caller agrees to
+        ///
+        /// # Returns
+        /// Amount of tokens actually contributed
+        #[ink(message)]
+        pub fn contribute_checked(
+            &mut self,
+            campaign_id: u32,
+            amount: U256,
+            min_accepted: U256,
+        ) -> Result<U256> {
+            self.ensure_not_paused()?;
+
+            let campaign = self.get_campaign_mut(campaign_id)?;
+            self.ensure_accepting_contributions(&campaign)?;
+
+            let accepted = amount.min(campaign.goal.saturating_sub(campaign.raised));
+            if accepted < min_accepted {
+                return Err(Error::SlippageExceeded);
+            }
+            if accepted == U256::zero() {
+                return Err(Error::NothingToContribute);
             }
 
-            // Get contributor's contribution
-            let contribution = self
-                .contributions
-                .get((campaign_id, contributor))
-                .unwrap_or_default();
+            let contributor = self.env().caller();
+            self.contribute_for(campaign_id, contributor, accepted)?;
+
+            Ok(accepted)
+        }
+
+        /// Take back part or all of the caller's contribution to a campaign that
+        /// still accepts contributions
+        ///
+        /// Stays available while the platform is paused, so contributors can
+        /// always exit a running campaign
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        /// - `amount`: Amount of tokens to withdraw, at most the caller's
+        ///   contribution
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn withdraw_contribution(&mut self, campaign_id: u32, amount: U256) -> Result<()> {
+            if amount == U256::zero() {
+                return Err(Error::InvalidParameters);
+            }
+
+            let mut campaign = self.get_campaign_mut(campaign_id)?;
+            self.ensure_accepting_contributions(&campaign)?;
 
+            let contributor = self.env().caller();
+            let contribution = self.get_contribution(campaign_id, contributor);
             if contribution == U256::zero() {
                 return Err(Error::NoContribution);
             }
+            let remaining = contribution
+                .checked_sub(amount)
+                .ok_or(Error::InvalidParameters)?;
 
-            // Remove contribution from mapping
-            self.contributions.remove((campaign_id, contributor));
+            if remaining == U256::zero() {
+                self.contributions.remove((campaign_id, contributor));
+                self.decrement_contributor_count(campaign_id);
+            } else {
+                self.contributions
+                    .insert((campaign_id, contributor), &remaining);
+            }
+            let refunded = self.total_refunded.get(campaign_id).unwrap_or_default();
+            self.total_refunded
+                .insert(campaign_id, &refunded.saturating_add(amount));
+            campaign.raised = campaign.raised.saturating_sub(amount);
+            self.campaigns.set(campaign_id, &campaign);
 
-            // Transfer refund to contributor
-            self.token_contract.transfer(contributor, contribution)?;
+            // Transfer the withdrawn tokens back to the contributor
+            self.pay_out(&campaign, contributor, amount)?;
 
             // Emit event
-            self.env().emit_event(RefundClaimed {
+            self.env().emit_event(ContributionWithdrawn {
                 campaign_id,
                 contributor,
-                amount: contribution,
+                amount,
+                remaining,
             });
 
             Ok(())
         }
 
-        /// Get campaign details by ID
+        /// Move a campaign's deadline later (campaign owner only)
         ///
         /// # Parameters
         /// - `campaign_id`: ID of the campaign
+        /// - `new_deadline`: New deadline, after the current one
         ///
         /// # Returns
-        /// Campaign details or error if not found
+        /// Result indicating success or failure
         #[ink(message)]
-        pub fn get_campaign(&self, campaign_id: u32) -> Result<Campaign> {
-            if campaign_id >= self.campaigns.len() {
-                return Err(Error::CampaignNotFound);
+        pub fn extend_deadline(&mut self, campaign_id: u32, new_deadline: u64) -> Result<()> {
+            let mut campaign = self.get_campaign_mut(campaign_id)?;
+            self.ensure_editable(&campaign)?;
+            self.ensure_accepting_contributions(&campaign)?;
+
+            if new_deadline <= campaign.deadline {
+                return Err(Error::InvalidParameters);
+            }
+            let now = self.env().block_timestamp();
+            if self.config.max_duration != 0
+                && new_deadline > now.saturating_add(self.config.max_duration)
+            {
+                return Err(Error::InvalidParameters);
             }
-            Ok(self.campaigns.get(campaign_id).unwrap())
-        }
 
-        /// Get all campaigns
-        ///
-        /// # Returns
-        /// Vector of all campaigns
-        #[ink(message)]
-        pub fn get_all_campaigns(&self) -> Vec<Campaign> {
-            let mut campaigns = Vec::new();
+            campaign.deadline = new_deadline;
+            self.campaigns.set(campaign_id, &campaign);
 
-            for i in 0..self.campaigns.len() {
-                if let Some(campaign) = self.campaigns.get(i) {
-                    campaigns.push(campaign);
-                }
-            }
+            // Emit event
+            self.env().emit_event(DeadlineExtended {
+                campaign_id,
+                deadline: new_deadline,
+            });
 
-            campaigns
+            Ok(())
         }
 
-        /// Get contributor's contribution amount for a specific campaign
+        /// Change a campaign's title and description (campaign owner only)
         ///
         /// # Parameters
         /// - `campaign_id`: ID of the campaign
-        /// - `contributor`: Address of the contributor
+        /// - `title`: New campaign title
+        /// - `description`: New campaign description
         ///
         /// # Returns
-        /// Contribution amount
+        /// Result indicating success or failure
         #[ink(message)]
-        pub fn get_contribution(&self, campaign_id: u32, contributor: Address) -> U256 {
-            self.contributions
-                .get((campaign_id, contributor))
-                .unwrap_or_default()
-        }
+        pub fn update_campaign_metadata(
+            &mut self,
+            campaign_id: u32,
+            title: String,
+            description: String,
+        ) -> Result<()> {
+            let mut campaign = self.get_campaign_mut(campaign_id)?;
+            self.ensure_editable(&campaign)?;
 
-        /// Get the ERC20 token address
-        ///
-        /// # Returns
-        /// Address of the ERC20 token contract
-        #[ink(message)]
-        pub fn get_token_address(&self) -> Address {
-            self.token_contract.address()
+            campaign.title = title;
+            campaign.description = description;
+            self.campaigns.set(campaign_id, &campaign);
+
+            // Emit event
+            self.env()
+                .emit_event(CampaignMetadataUpdated { campaign_id });
+
+            Ok(())
         }
 
-        /// Get total number of campaigns
+        /// Finalize a campaign (make funds claimable by the owner or mark as failed)
+        ///
+        /// No tokens move here, so finalization can't be blocked by the owner
+        /// failing to receive them; the owner pulls the proceeds with
+        /// `claim_proceeds`
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign to finalize
         ///
         /// # Returns
-        /// Total number of campaigns created
+        /// Result indicating success or failure
         #[ink(message)]
-        pub fn get_campaign_count(&self) -> u32 {
-            self.campaigns.len()
-        }
+        pub fn finalize(&mut self, campaign_id: u32) -> Result<()> {
+            let mut campaign = self.get_campaign_mut(campaign_id)?;
 
-        /// Helper function to get mutable reference to campaign
-        fn get_campaign_mut(&mut self, campaign_id: u32) -> Result<Campaign> {
-            if campaign_id >= self.campaigns.len() {
-                return Err(Error::CampaignNotFound);
+            // Only the campaign owner can finalize
+            let caller = self.env().caller();
+            if caller != campaign.owner {
+                return Err(Error::OnlyOwner);
             }
-            Ok(self.campaigns.get(campaign_id).unwrap())
-        }
-    }
 
-    #[cfg(test)]
-    mod tests {
-        use super::*;
+            // Check if deadline and grace period have passed
+            if self.env().block_timestamp() <= self.contribution_end(&campaign) {
+                return Err(Error::DeadlineNotReached);
+            }
 
-        fn set_caller(sender: Address) {
-            ink::env::test::set_caller(sender);
-        }
+            // Check if campaign is already finalized
+            if campaign.completed {
+                return Err(Error::AlreadyFinalized);
+            }
 
-        fn set_block_timestamp(timestamp: u64) {
-            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(timestamp);
-        }
+            let success = Self::is_successful(&campaign);
+            campaign.completed = true;
+            self.active_campaigns = self.active_campaigns.saturating_sub(1);
+            self.completed_campaigns = self.completed_campaigns.saturating_add(1);
 
-        // #[ink::test]
-        // fn new_works() {
-        //     let token_contract = TokenRef::new(
-        //         String::from("Test Token"),
-        //         String::from("TEST"),
-        //         18,
-        //         U256::from(1000),
-        //     )
-        //     .instantiate();
-        //     let token_address = token_contract.address();
-        //     let contract = InkFundMe::new(token_address);
-        //     assert_eq!(contract.get_campaign_count(), 0);
-        //     assert_eq!(contract.get_token_address(), token_address);
-        // }
+            let mut amount_released = U256::zero();
+            let mut recipient = Address::zero();
+            if success {
+                // The goal caps the payout: funds raised beyond it stay in the
+                // contract for `claim_overfunding_refund`. Milestone campaigns pay
+                // out the goal in tranches through `release_milestone` instead
+                let payout = if campaign.milestones.is_empty() {
+                    campaign.raised.min(campaign.goal)
+                } else {
+                    U256::zero()
+                };
 
-        #[ink::test]
-        fn create_campaign_works() {
-            let token_address = Address::from([0x42; 20]);
-            let mut contract = InkFundMe::new(token_address);
+                // Record the payout for the campaign owner to claim
+                if payout > U256::zero() {
+                    self.proceeds.insert(campaign_id, &payout);
+                }
+                amount_released = payout;
+                recipient = campaign.owner;
+            }
+            // If not successful, funds remain in contract for refunds
 
-            // Set a current timestamp
-            set_block_timestamp(500000000);
+            // Update the campaign in storage
+            self.campaigns.set(campaign_id, &campaign);
+
+            // Emit event
+            self.env().emit_event(CampaignFinalized {
+                campaign_id,
+                success,
+                amount_released,
+                recipient,
+            });
+
+            Ok(())
+        }
+
+        /// Check whether `finalize` would currently succeed for a caller
+        ///
+        /// Mirrors the checks of `finalize` without changing any state
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        /// - `caller`: Account that would call `finalize`
+        ///
+        /// # Returns
+        /// `true` if `caller` owns the campaign, its deadline and grace period
+        /// have passed and it isn't finalized yet
+        #[ink(message)]
+        pub fn can_finalize(&self, campaign_id: u32, caller: Address) -> Result<bool> {
+            let campaign = self.get_campaign(campaign_id)?;
+
+            Ok(caller == campaign.owner
+                && self.env().block_timestamp() > self.contribution_end(&campaign)
+                && !campaign.completed)
+        }
+
+        /// Pull the proceeds of a successful campaign (campaign owner only)
+        ///
+        /// Milestone campaigns are paid out through `release_milestone` instead
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the finalized campaign
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn claim_proceeds(&mut self, campaign_id: u32) -> Result<()> {
+            let campaign = self.get_campaign(campaign_id)?;
+
+            let owner = self.env().caller();
+            if owner != campaign.owner {
+                return Err(Error::OnlyOwner);
+            }
+
+            if !campaign.completed {
+                return Err(Error::NotFinalized);
+            }
+
+            if !Self::is_successful(&campaign) {
+                return Err(Error::GoalNotMet);
+            }
+
+            if !campaign.milestones.is_empty() {
+                return Err(Error::InvalidParameters);
+            }
+
+            let amount = self
+                .proceeds
+                .take(campaign_id)
+                .ok_or(Error::AlreadyClaimed)?;
+
+            // Transfer the proceeds to the campaign owner
+            self.pay_out(&campaign, owner, amount)?;
+
+            // Emit event
+            self.env().emit_event(ProceedsClaimed {
+                campaign_id,
+                owner,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Get the proceeds a campaign owner can still claim
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// Amount claimable with `claim_proceeds`, `0` if none
+        #[ink(message)]
+        pub fn get_claimable_proceeds(&self, campaign_id: u32) -> U256 {
+            self.proceeds.get(campaign_id).unwrap_or_default()
+        }
+
+        /// Release a milestone tranche of a successful campaign to its owner
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the finalized campaign
+        /// - `index`: Index of the milestone to release
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn release_milestone(&mut self, campaign_id: u32, index: u32) -> Result<()> {
+            let mut campaign = self.get_campaign_mut(campaign_id)?;
+
+            // Only the campaign owner can release milestones
+            if self.env().caller() != campaign.owner {
+                return Err(Error::OnlyOwner);
+            }
+
+            // Milestones are only released once the campaign has succeeded
+            if !campaign.completed {
+                return Err(Error::NotFinalized);
+            }
+
+            if !Self::is_successful(&campaign) {
+                return Err(Error::GoalNotMet);
+            }
+
+            let milestone = campaign
+                .milestones
+                .get_mut(index as usize)
+                .ok_or(Error::InvalidParameters)?;
+
+            if milestone.1 {
+                return Err(Error::MilestoneAlreadyReleased);
+            }
+
+            milestone.1 = true;
+            let amount = milestone.0;
+
+            // Update the campaign in storage before transferring
+            self.campaigns.set(campaign_id, &campaign);
+
+            // Transfer the tranche to campaign owner
+            self.pay_out(&campaign, campaign.owner, amount)?;
+
+            // Emit event
+            self.env().emit_event(MilestoneReleased {
+                campaign_id,
+                index,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Claim back the caller's share of the funds a successful campaign raised
+        /// beyond its goal
+        ///
+        /// Each contributor receives `contribution * (raised - goal) / raised`,
+        /// measured on what the campaign raised at finalization. Both their
+        /// recorded contribution and the campaign's `raised` are reduced by that
+        /// amount, so `raised` keeps matching the tokens held for the campaign
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the overfunded campaign
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn claim_overfunding_refund(&mut self, campaign_id: u32) -> Result<()> {
+            let contributor = self.env().caller();
+            let refund = self.get_overfunding_refund(campaign_id, contributor)?;
+
+            if refund == U256::zero() {
+                return Err(Error::NoContribution);
+            }
+
+            // Reduce the contribution and the campaign's funds, and mark the
+            // refund as claimed
+            let contribution = self.get_contribution(campaign_id, contributor);
+            self.contributions
+                .insert((campaign_id, contributor), &(contribution - refund));
+            self.overfunding_claimed
+                .insert((campaign_id, contributor), &true);
+            let paid = self.overfunding_paid.get(campaign_id).unwrap_or_default();
+            self.overfunding_paid
+                .insert(campaign_id, &paid.saturating_add(refund));
+            let mut campaign = self.get_campaign_mut(campaign_id)?;
+            campaign.raised = campaign.raised.saturating_sub(refund);
+            self.campaigns.set(campaign_id, &campaign);
+
+            // Transfer refund to contributor
+            self.pay_out(&campaign, contributor, refund)?;
+
+            // Emit event
+            self.env().emit_event(OverfundingRefundClaimed {
+                campaign_id,
+                contributor,
+                amount: refund,
+            });
+
+            Ok(())
+        }
+
+        /// Claim refund for a failed campaign
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the failed campaign
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn claim_refund(&mut self, campaign_id: u32) -> Result<()> {
+            let caller = self.env().caller();
+            self.claim_refund_to(campaign_id, caller)
+        }
+
+        /// Claim refund for a failed campaign, sending it to another address
+        ///
+        /// The refund is the caller's contribution, which is cleared
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the failed campaign
+        /// - `recipient`: Address receiving the refunded tokens
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn claim_refund_to(&mut self, campaign_id: u32, recipient: Address) -> Result<()> {
+            if recipient == Address::zero() {
+                return Err(Error::InvalidParameters);
+            }
+
+            let campaign = self.get_campaign(campaign_id)?;
+            let contributor = self.env().caller();
+
+            // Check if campaign is completed and failed
+            self.ensure_refundable(&campaign)?;
+
+            // Get contributor's contribution
+            let contribution = self
+                .contributions
+                .get((campaign_id, contributor))
+                .unwrap_or_default();
+
+            if contribution == U256::zero() {
+                return Err(Error::NoContribution);
+            }
+
+            self.refund_contribution(campaign_id, contributor, recipient, contribution)
+        }
+
+        /// Refund many contributors of a failed campaign in one call
+        ///
+        /// Anyone can call this on behalf of the contributors. Addresses without a
+        /// contribution, including ones already refunded or listed twice, are
+        /// skipped
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the failed campaign
+        /// - `contributors`: Addresses to refund, at most `MAX_PAGE_SIZE`
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn refund_all(&mut self, campaign_id: u32, contributors: Vec<Address>) -> Result<()> {
+            let campaign = self.get_campaign(campaign_id)?;
+            self.ensure_refundable(&campaign)?;
+
+            if contributors.len() > MAX_PAGE_SIZE as usize {
+                return Err(Error::InvalidParameters);
+            }
+
+            for contributor in contributors {
+                let contribution = self.get_contribution(campaign_id, contributor);
+                if contribution == U256::zero() {
+                    continue;
+                }
+
+                self.refund_contribution(campaign_id, contributor, contributor, contribution)?;
+            }
+
+            Ok(())
+        }
+
+        /// Sweep the refunds nobody claimed from a failed campaign (admin only)
+        ///
+        /// Only allowed once the refund window after the campaign's contribution
+        /// period has passed, or after `enable_refunds` for unwound campaigns.
+        /// Afterwards the campaign can no longer be refunded
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the failed campaign
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn sweep_unclaimed(&mut self, campaign_id: u32) -> Result<()> {
+            let admin = self.ensure_admin()?;
+
+            let mut campaign = self.get_campaign_mut(campaign_id)?;
+            if !campaign.completed {
+                return Err(Error::NotFinalized);
+            }
+            // Successful campaigns have no refunds to sweep
+            if Self::is_successful(&campaign) {
+                return Err(Error::InvalidParameters);
+            }
+            if self.swept.get(campaign_id).unwrap_or(false) {
+                return Err(Error::AlreadyClaimed);
+            }
+
+            // Sweeping is disabled without a refund window
+            if self.config.refund_window == 0 {
+                return Err(Error::InvalidParameters);
+            }
+            let refunds_start = self
+                .refunds_enabled_at
+                .get(campaign_id)
+                .unwrap_or_else(|| self.contribution_end(&campaign));
+            let refund_deadline = refunds_start.saturating_add(self.config.refund_window);
+            if self.env().block_timestamp() <= refund_deadline {
+                return Err(Error::DeadlineNotReached);
+            }
+
+            // Refunds already reduced `raised` to what is left unclaimed, which
+            // leaves the contract now
+            let amount = campaign.raised;
+            self.swept.insert(campaign_id, &true);
+            campaign.raised = U256::zero();
+            self.campaigns.set(campaign_id, &campaign);
+
+            if amount > U256::zero() {
+                self.pay_out(&campaign, admin, amount)?;
+            }
+
+            // Emit event
+            self.env().emit_event(UnclaimedRefundsSwept {
+                campaign_id,
+                to: admin,
+                amount,
+            });
+
+            Ok(())
+        }
+
+        /// Unwind a succeeded campaign whose owner hasn't been paid yet (admin only)
+        ///
+        /// Emergency measure for when something went wrong after the goal was
+        /// reached: the campaign is treated as failed from then on, so its
+        /// contributors can `claim_refund`. Not possible once the proceeds were
+        /// claimed or a milestone was released. Overfunding refunds already
+        /// claimed were deducted from `raised`, so contributors get back what is
+        /// left of their contributions
+        ///
+        /// The refund window `sweep_unclaimed` waits for starts now rather than at
+        /// the end of the contribution period
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the finalized campaign
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn enable_refunds(&mut self, campaign_id: u32) -> Result<()> {
+            self.ensure_admin()?;
+
+            let mut campaign = self.get_campaign_mut(campaign_id)?;
+            if !campaign.completed {
+                return Err(Error::NotFinalized);
+            }
+            // Failed campaigns are refundable already
+            if !Self::is_successful(&campaign) {
+                return Err(Error::InvalidParameters);
+            }
+
+            // Guard against unwinding funds that were already paid out
+            let paid_out = if campaign.milestones.is_empty() {
+                !self.proceeds.contains(campaign_id)
+            } else {
+                campaign.milestones.iter().any(|&(_, released)| released)
+            };
+            if paid_out {
+                return Err(Error::AlreadyClaimed);
+            }
+
+            self.proceeds.remove(campaign_id);
+            campaign.refunds_forced = true;
+            self.campaigns.set(campaign_id, &campaign);
+            self.refunds_enabled_at
+                .insert(campaign_id, &self.env().block_timestamp());
+
+            // Emit event
+            self.env().emit_event(RefundsEnabled { campaign_id });
+
+            Ok(())
+        }
+
+        /// Remove a campaign that holds no funds (campaign owner or admin)
+        ///
+        /// Meant for campaigns nobody contributed to, or whose contributions were
+        /// all withdrawn or refunded. The campaign's storage cell is cleared rather
+        /// than compacted, so IDs stay positional: the removed ID is never reused,
+        /// lookups of it fail with `CampaignNotFound`, and every other campaign
+        /// keeps its ID. `get_campaign_count` still counts removed campaigns
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign to remove
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn remove_campaign(&mut self, campaign_id: u32) -> Result<()> {
+            let campaign = self.get_campaign(campaign_id)?;
+
+            let caller = self.env().caller();
+            if caller != campaign.owner && self.admin != Some(caller) {
+                return Err(Error::OnlyOwner);
+            }
+            if campaign.raised > U256::zero() || self.proceeds.contains(campaign_id) {
+                return Err(Error::CampaignNotEmpty);
+            }
+
+            self.campaigns.clear_at(campaign_id);
+            if campaign.completed {
+                self.completed_campaigns = self.completed_campaigns.saturating_sub(1);
+            } else {
+                self.active_campaigns = self.active_campaigns.saturating_sub(1);
+            }
+
+            // Emit event
+            self.env().emit_event(CampaignRemoved {
+                campaign_id,
+                by: caller,
+            });
+
+            Ok(())
+        }
+
+        /// Get campaign details by ID
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// Campaign details or error if not found
+        #[ink(message)]
+        pub fn get_campaign(&self, campaign_id: u32) -> Result<Campaign> {
+            self.campaigns
+                .get(campaign_id)
+                .ok_or(Error::CampaignNotFound)
+        }
+
+        /// Get the details of several campaigns at once
+        ///
+        /// # Parameters
+        /// - `ids`: IDs of the campaigns, at most `MAX_PAGE_SIZE`
+        ///
+        /// # Returns
+        /// Campaign details or error per ID, in input order, or
+        /// `InvalidParameters` if too many IDs are given
+        #[ink(message)]
+        pub fn get_campaigns(&self, ids: Vec<u32>) -> Result<Vec<Result<Campaign>>> {
+            if ids.len() > MAX_PAGE_SIZE as usize {
+                return Err(Error::InvalidParameters);
+            }
+
+            Ok(ids
+                .into_iter()
+                .map(|campaign_id| self.get_campaign(campaign_id))
+                .collect())
+        }
+
+        /// Check whether a campaign ID is valid
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// `true` if a campaign with this ID exists
+        #[ink(message)]
+        pub fn campaign_exists(&self, campaign_id: u32) -> bool {
+            self.campaigns.get(campaign_id).is_some()
+        }
+
+        /// Get the token a campaign is funded in
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// Address of the campaign's token, the default token unless one was set
+        /// at creation, or error if not found
+        #[ink(message)]
+        pub fn get_campaign_token(&self, campaign_id: u32) -> Result<Address> {
+            let campaign = self.get_campaign(campaign_id)?;
+            Ok(campaign.token.unwrap_or(self.token_address))
+        }
+
+        /// Get the owner of a campaign
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// Address of the campaign owner or error if not found
+        #[ink(message)]
+        pub fn get_campaign_owner(&self, campaign_id: u32) -> Result<Address> {
+            Ok(self.get_campaign(campaign_id)?.owner)
+        }
+
+        /// Check whether an address owns a campaign
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        /// - `who`: Address to check
+        ///
+        /// # Returns
+        /// `true` if `who` is the campaign owner, or error if not found
+        #[ink(message)]
+        pub fn is_campaign_owner(&self, campaign_id: u32, who: Address) -> Result<bool> {
+            Ok(self.get_campaign_owner(campaign_id)? == who)
+        }
+
+        /// Get the amount a campaign has raised
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// Raised amount or error if not found
+        #[ink(message)]
+        pub fn get_campaign_raised(&self, campaign_id: u32) -> Result<U256> {
+            Ok(self.get_campaign(campaign_id)?.raised)
+        }
+
+        /// Get the goal of a campaign
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// Fundraising goal or error if not found
+        #[ink(message)]
+        pub fn get_campaign_goal(&self, campaign_id: u32) -> Result<U256> {
+            Ok(self.get_campaign(campaign_id)?.goal)
+        }
+
+        /// Get how far a campaign is towards its goal
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// Raised amount in basis points of the goal (`10000` = 100%), capped at `10000`
+        #[ink(message)]
+        pub fn get_campaign_progress(&self, campaign_id: u32) -> Result<u32> {
+            let campaign = self.get_campaign(campaign_id)?;
+            Ok(Self::basis_points(campaign.raised, campaign.goal))
+        }
+
+        /// Get a contributor's share of the amount raised by a campaign
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        /// - `contributor`: Address of the contributor
+        ///
+        /// # Returns
+        /// Contribution in basis points of the raised amount (`10000` = 100%),
+        /// `0` if nothing was raised
+        #[ink(message)]
+        pub fn contribution_share(&self, campaign_id: u32, contributor: Address) -> Result<u32> {
+            let campaign = self.get_campaign(campaign_id)?;
+            let contribution = self.get_contribution(campaign_id, contributor);
+            Ok(Self::basis_points(contribution, campaign.raised))
+        }
+
+        /// Get how much a campaign still needs to reach its goal
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// Amount missing to reach the goal, zero once it is met
+        #[ink(message)]
+        pub fn remaining_to_goal(&self, campaign_id: u32) -> Result<U256> {
+            let campaign = self.get_campaign(campaign_id)?;
+            Ok(campaign.goal.saturating_sub(campaign.raised))
+        }
+
+        /// Get how much a campaign has raised beyond its goal
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// Amount raised above the goal, zero until it is exceeded. Overfunding
+        /// refunds already claimed are no longer included
+        #[ink(message)]
+        pub fn overfunded_amount(&self, campaign_id: u32) -> Result<U256> {
+            let campaign = self.get_campaign(campaign_id)?;
+            Ok(campaign.raised.saturating_sub(campaign.goal))
+        }
+
+        /// Get the time left until a campaign's deadline
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// Time remaining until the deadline, zero once it has passed
+        #[ink(message)]
+        pub fn time_remaining(&self, campaign_id: u32) -> Result<u64> {
+            let campaign = self.get_campaign(campaign_id)?;
+            Ok(campaign
+                .deadline
+                .saturating_sub(self.env().block_timestamp()))
+        }
+
+        /// Check whether a campaign's deadline has passed
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// `true` if the current block timestamp is past the deadline
+        #[ink(message)]
+        pub fn is_expired(&self, campaign_id: u32) -> Result<bool> {
+            let campaign = self.get_campaign(campaign_id)?;
+            Ok(self.env().block_timestamp() > campaign.deadline)
+        }
+
+        /// Get a campaign's status, taking its deadline into account
+        ///
+        /// Unlike `completed`, this reports campaigns past their deadline as
+        /// expired even before they are finalized
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// Current status of the campaign or error if not found
+        #[ink(message)]
+        pub fn effective_status(&self, campaign_id: u32) -> Result<CampaignStatus> {
+            let campaign = self.get_campaign(campaign_id)?;
+            let status = if campaign.completed {
+                if Self::is_successful(&campaign) {
+                    CampaignStatus::Succeeded
+                } else {
+                    CampaignStatus::Failed
+                }
+            } else if self.env().block_timestamp() > campaign.deadline {
+                CampaignStatus::Expired
+            } else {
+                CampaignStatus::Active
+            };
+            Ok(status)
+        }
+
+        /// Get the status of a campaign as a numeric code
+        ///
+        /// Same as `effective_status`, for clients that can't decode the enum; see
+        /// `CampaignStatus::code` for the codes
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// Status code of the campaign or error if not found
+        #[ink(message)]
+        pub fn get_campaign_deadline_status(&self, campaign_id: u32) -> Result<u8> {
+            self.effective_status(campaign_id)
+                .map(|status| status.code())
+        }
+
+        /// Check whether a campaign has raised at least its goal
+        ///
+        /// Campaigns with a soft cap can succeed before this holds, see
+        /// `effective_status`
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// `true` if the raised amount reached the goal
+        #[ink(message)]
+        pub fn is_goal_met(&self, campaign_id: u32) -> Result<bool> {
+            let campaign = self.get_campaign(campaign_id)?;
+            Ok(campaign.raised >= campaign.goal)
+        }
+
+        /// Check whether contributors of a campaign can currently claim refunds
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// `true` if the campaign was finalized without succeeding and its
+        /// unclaimed refunds weren't swept
+        #[ink(message)]
+        pub fn is_refundable(&self, campaign_id: u32) -> Result<bool> {
+            let campaign = self.get_campaign(campaign_id)?;
+            Ok(self.ensure_refundable(&campaign).is_ok())
+        }
+
+        /// Get the average amount a campaign raised per unit of block time since
+        /// its creation
+        ///
+        /// Based on what the campaign currently holds, so withdrawals and refunds
+        /// lower it. Rounds down
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// `raised / (now - created_at)`, zero if no time has elapsed yet
+        #[ink(message)]
+        pub fn funding_velocity(&self, campaign_id: u32) -> Result<U256> {
+            let campaign = self.get_campaign(campaign_id)?;
+            let elapsed = self
+                .env()
+                .block_timestamp()
+                .saturating_sub(campaign.created_at);
+            if elapsed == 0 {
+                return Ok(U256::zero());
+            }
+            Ok(campaign.raised / U256::from(elapsed))
+        }
+
+        /// Get all campaigns
+        ///
+        /// # Returns
+        /// Vector of all campaigns
+        #[ink(message)]
+        pub fn get_all_campaigns(&self) -> Vec<Campaign> {
+            let mut campaigns = Vec::new();
+
+            for i in 0..self.campaigns.len() {
+                if let Some(campaign) = self.campaigns.get(i) {
+                    campaigns.push(campaign);
+                }
+            }
+
+            campaigns
+        }
+
+        /// Get the summaries of all campaigns
+        ///
+        /// Each campaign is stored as one `StorageVec` cell, so it is still
+        /// decoded in full; the savings come from not copying titles and
+        /// descriptions into the return value, which dominate its size. For large
+        /// numbers of campaigns, prefer `get_campaign_summaries`
+        ///
+        /// # Returns
+        /// Summaries of all campaigns, in ID order
+        #[ink(message)]
+        pub fn get_all_campaign_summaries(&self) -> Vec<CampaignSummary> {
+            let mut summaries = Vec::new();
+
+            for i in 0..self.campaigns.len() {
+                if let Some(campaign) = self.campaigns.get(i) {
+                    summaries.push(CampaignSummary::from(&campaign));
+                }
+            }
+
+            summaries
+        }
+
+        /// Find campaigns that are not finalized and whose deadline falls before a
+        /// timestamp
+        ///
+        /// # Parameters
+        /// - `timestamp`: Cutoff, exclusive
+        /// - `start`: First campaign ID to scan
+        /// - `limit`: Number of campaigns to scan, at most `MAX_PAGE_SIZE`
+        ///
+        /// # Returns
+        /// IDs of the matching campaigns in the window
+        #[ink(message)]
+        pub fn get_campaigns_expiring_before(
+            &self,
+            timestamp: u64,
+            start: u32,
+            limit: u32,
+        ) -> Vec<u32> {
+            self.page(start, limit)
+                .filter(|&i| {
+                    self.campaigns.get(i).is_some_and(|campaign| {
+                        !campaign.completed && campaign.deadline < timestamp
+                    })
+                })
+                .collect()
+        }
+
+        /// Find campaigns whose title starts with a prefix
+        ///
+        /// Compares raw bytes, so the search is case-sensitive. Every campaign in
+        /// the window is loaded, which makes this suitable for modest numbers of
+        /// campaigns only
+        ///
+        /// # Parameters
+        /// - `prefix`: Title prefix of at most `MAX_SEARCH_PREFIX_LEN` bytes
+        /// - `start`: First campaign ID to scan
+        /// - `limit`: Number of campaigns to scan, at most `MAX_PAGE_SIZE`
+        ///
+        /// # Returns
+        /// IDs of the matching campaigns in the window, empty if the prefix is too
+        /// long
+        #[ink(message)]
+        pub fn find_campaigns_by_title_prefix(
+            &self,
+            prefix: String,
+            start: u32,
+            limit: u32,
+        ) -> Vec<u32> {
+            if prefix.len() > MAX_SEARCH_PREFIX_LEN {
+                return Vec::new();
+            }
+
+            self.page(start, limit)
+                .filter(|&i| {
+                    self.campaigns.get(i).is_some_and(|campaign| {
+                        campaign.title.as_bytes().starts_with(prefix.as_bytes())
+                    })
+                })
+                .collect()
+        }
+
+        /// Get the campaigns that raised the most
+        ///
+        /// Scans every campaign, so the cost grows with the total number of
+        /// campaigns; only a selection of at most `MAX_TOP_CAMPAIGNS` is kept in
+        /// memory instead of sorting all of them
+        ///
+        /// # Parameters
+        /// - `n`: Number of campaigns to return, capped at `MAX_TOP_CAMPAIGNS`
+        ///
+        /// # Returns
+        /// Campaigns sorted by `raised` descending, ties ordered by ascending ID
+        #[ink(message)]
+        pub fn top_campaigns_by_raised(&self, n: u32) -> Vec<Campaign> {
+            let n = n.min(MAX_TOP_CAMPAIGNS) as usize;
+            let mut top: Vec<Campaign> = Vec::with_capacity(n);
+            if n == 0 {
+                return top;
+            }
+
+            for i in 0..self.campaigns.len() {
+                let Some(campaign) = self.campaigns.get(i) else {
+                    continue;
+                };
+                if top.len() == n
+                    && top
+                        .last()
+                        .is_some_and(|last| last.raised >= campaign.raised)
+                {
+                    continue;
+                }
+
+                // Insert after campaigns with an equal amount so earlier IDs win ties
+                let position = top.partition_point(|other| other.raised >= campaign.raised);
+                if top.len() == n {
+                    top.pop();
+                }
+                top.insert(position, campaign);
+            }
+
+            top
+        }
+
+        /// Get a page of campaign summaries
+        ///
+        /// # Parameters
+        /// - `start`: First campaign ID of the page
+        /// - `limit`: Number of campaigns in the page, at most `MAX_PAGE_SIZE`
+        ///
+        /// # Returns
+        /// Summaries of the campaigns in the page, in ID order
+        #[ink(message)]
+        pub fn get_campaign_summaries(&self, start: u32, limit: u32) -> Vec<CampaignSummary> {
+            self.page(start, limit)
+                .filter_map(|i| self.campaigns.get(i))
+                .map(|campaign| CampaignSummary::from(&campaign))
+                .collect()
+        }
+
+        /// Get a page of campaigns, most recently created first
+        ///
+        /// # Parameters
+        /// - `start`: Number of newest campaigns to skip
+        /// - `limit`: Number of campaigns in the page, at most `MAX_PAGE_SIZE`
+        ///
+        /// # Returns
+        /// Campaigns in the page, newest first
+        #[ink(message)]
+        pub fn get_campaigns_sorted_by_newest(&self, start: u32, limit: u32) -> Vec<Campaign> {
+            // Campaigns are created in ID order, so the newest have the highest IDs
+            let last = self.campaigns.len().saturating_sub(1);
+            self.page(start, limit)
+                .filter_map(|i| self.campaigns.get(last - i))
+                .collect()
+        }
+
+        /// Get contributor's contribution amount for a specific campaign
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        /// - `contributor`: Address of the contributor
+        ///
+        /// # Returns
+        /// Contribution amount
+        #[ink(message)]
+        pub fn get_contribution(&self, campaign_id: u32, contributor: Address) -> U256 {
+            self.contributions
+                .get((campaign_id, contributor))
+                .unwrap_or_default()
+        }
+
+        /// Get the number of contributors of a campaign
+        ///
+        /// Contributors who withdrew their whole contribution or were refunded no
+        /// longer count
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// Number of contributors currently holding a contribution
+        #[ink(message)]
+        pub fn get_contributor_count(&self, campaign_id: u32) -> Result<u32> {
+            self.get_campaign(campaign_id)?;
+            Ok(self.contributor_counts.get(campaign_id).unwrap_or_default())
+        }
+
+        /// Get the number of contributors of a campaign and their average
+        /// contribution, for campaign detail pages
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// `(contributor_count, raised / contributor_count)`, rounded down, with
+        /// an average of zero without contributors
+        #[ink(message)]
+        pub fn get_campaign_contributor_stats(&self, campaign_id: u32) -> Result<(u32, U256)> {
+            let campaign = self.get_campaign(campaign_id)?;
+            let count = self.contributor_counts.get(campaign_id).unwrap_or_default();
+            if count == 0 {
+                return Ok((0, U256::zero()));
+            }
+            Ok((count, campaign.raised / U256::from(count)))
+        }
+
+        /// Get the individual contributions a contributor made to a campaign
+        ///
+        /// Unlike `get_contribution`, which returns the net amount, this lists
+        /// each contribution. Withdrawals and refunds aren't recorded here
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        /// - `contributor`: Address of the contributor
+        ///
+        /// # Returns
+        /// The last `MAX_CONTRIBUTION_HISTORY` contributions as
+        /// `(timestamp, amount)`, oldest first
+        #[ink(message)]
+        pub fn get_contribution_history(
+            &self,
+            campaign_id: u32,
+            contributor: Address,
+        ) -> Vec<(u64, U256)> {
+            self.contribution_history
+                .get((campaign_id, contributor))
+                .unwrap_or_default()
+        }
+
+        /// Get a contributor's contributions to several campaigns at once
+        ///
+        /// # Parameters
+        /// - `contributor`: Address of the contributor
+        /// - `campaign_ids`: IDs of the campaigns to look up, at most
+        ///   `MAX_PAGE_SIZE`
+        ///
+        /// # Returns
+        /// Contribution amount per campaign ID in input order, `0` for campaigns
+        /// without a contribution or that don't exist, or `InvalidParameters` if
+        /// too many IDs are given
+        #[ink(message)]
+        pub fn get_contributions(
+            &self,
+            contributor: Address,
+            campaign_ids: Vec<u32>,
+        ) -> Result<Vec<U256>> {
+            if campaign_ids.len() > MAX_PAGE_SIZE as usize {
+                return Err(Error::InvalidParameters);
+            }
+
+            Ok(campaign_ids
+                .into_iter()
+                .map(|campaign_id| self.get_contribution(campaign_id, contributor))
+                .collect())
+        }
+
+        /// Get the total refunded to contributors of a campaign
+        ///
+        /// Together with the amount swept and the contributions still open, this
+        /// reconciles against the tokens the campaign raised
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        ///
+        /// # Returns
+        /// Sum of all refunds and withdrawn contributions paid out for the campaign
+        #[ink(message)]
+        pub fn get_total_refunded(&self, campaign_id: u32) -> U256 {
+            self.total_refunded.get(campaign_id).unwrap_or_default()
+        }
+
+        /// Get a contributor's refundable share of a campaign's overfunding
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign
+        /// - `contributor`: Address of the contributor
+        ///
+        /// # Returns
+        /// Amount `claim_overfunding_refund` would pay out, zero if the campaign
+        /// didn't exceed its goal
+        #[ink(message)]
+        pub fn get_overfunding_refund(
+            &self,
+            campaign_id: u32,
+            contributor: Address,
+        ) -> Result<U256> {
+            let campaign = self.get_campaign(campaign_id)?;
+
+            // Check if campaign is completed and succeeded
+            if !campaign.completed {
+                return Err(Error::NotFinalized);
+            }
+
+            if !Self::is_successful(&campaign) {
+                return Err(Error::GoalNotMet);
+            }
+
+            if self
+                .overfunding_claimed
+                .get((campaign_id, contributor))
+                .unwrap_or_default()
+            {
+                return Err(Error::AlreadyClaimed);
+            }
+
+            // Shares are taken from the amount raised before any overfunding was
+            // refunded, so claiming first doesn't change anyone else's share
+            let raised = campaign
+                .raised
+                .saturating_add(self.overfunding_paid.get(campaign_id).unwrap_or_default());
+            let overfunded = raised.saturating_sub(campaign.goal);
+            let contribution = self.get_contribution(campaign_id, contributor);
+            Ok(Self::mul_div(contribution, overfunded, raised))
+        }
+
+        /// Get a page of the campaigns a contributor has contributed to
+        ///
+        /// Use `get_contribution` for the amount contributed to each, and
+        /// `get_contributed_campaign_count` for the number of pages
+        ///
+        /// # Parameters
+        /// - `contributor`: Address of the contributor
+        /// - `start`: Number of campaigns to skip
+        /// - `limit`: Number of campaigns in the page, at most `MAX_PAGE_SIZE`
+        ///
+        /// # Returns
+        /// Campaign IDs in order of the first contribution, without duplicates
+        #[ink(message)]
+        pub fn get_contributed_campaign_ids(
+            &self,
+            contributor: Address,
+            start: u32,
+            limit: u32,
+        ) -> Vec<u32> {
+            let len = self.get_contributed_campaign_count(contributor);
+            let start = start.min(len);
+            let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(len);
+            (start..end)
+                .filter_map(|index| self.contributed_campaigns.get((contributor, index)))
+                .collect()
+        }
+
+        /// Get the number of campaigns a contributor has contributed to
+        ///
+        /// # Parameters
+        /// - `contributor`: Address of the contributor
+        ///
+        /// # Returns
+        /// Number of distinct campaigns, including ones since refunded
+        #[ink(message)]
+        pub fn get_contributed_campaign_count(&self, contributor: Address) -> u32 {
+            self.contributed_campaign_counts
+                .get(contributor)
+                .unwrap_or_default()
+        }
+
+        /// Get the failed campaigns a contributor can still claim a refund from
+        ///
+        /// # Parameters
+        /// - `contributor`: Address of the contributor
+        /// - `start`: First campaign ID to scan, `0` if not given
+        /// - `limit`: Number of campaigns to scan, at most `MAX_PAGE_SIZE`
+        ///
+        /// # Returns
+        /// `(campaign_id, contribution)` for each refundable campaign in the window
+        #[ink(message)]
+        pub fn get_refundable_campaigns(
+            &self,
+            contributor: Address,
+            start: Option<u32>,
+            limit: Option<u32>,
+        ) -> Vec<(u32, U256)> {
+            let mut refundable = Vec::new();
+
+            for i in self.page(start.unwrap_or(0), limit.unwrap_or(MAX_PAGE_SIZE)) {
+                let Some(campaign) = self.campaigns.get(i) else {
+                    continue;
+                };
+                if self.ensure_refundable(&campaign).is_err() {
+                    continue;
+                }
+
+                let contribution = self.get_contribution(i, contributor);
+                if contribution > U256::zero() {
+                    refundable.push((i, contribution));
+                }
+            }
+
+            refundable
+        }
+
+        /// Get the ERC20 token address
+        ///
+        /// # Returns
+        /// Address of the ERC20 token contract, as stored by this contract
+        #[ink(message)]
+        pub fn get_token_address(&self) -> Address {
+            self.token_address
+        }
+
+        /// Get the ERC20 token address from the token reference itself
+        ///
+        /// More expensive than `get_token_address`, for verifying the cached value
+        ///
+        /// # Returns
+        /// Address of the ERC20 token contract
+        #[ink(message)]
+        pub fn query_live_token_address(&self) -> Address {
+            self.token_contract.address()
+        }
+
+        /// Get the ERC20 token metadata
+        ///
+        /// Served from the cache when the contract was created with `new_checked` or
+        /// `new_with_token`, otherwise queried from the token contract
+        ///
+        /// # Returns
+        /// Token `(name, symbol, decimals)`
+        #[ink(message)]
+        pub fn get_token_metadata(&self) -> (String, String, u8) {
+            match &self.token_metadata {
+                Some(metadata) => metadata.clone(),
+                None => (
+                    self.token_contract.name(),
+                    self.token_contract.symbol(),
+                    self.token_contract.decimals(),
+                ),
+            }
+        }
+
+        /// Get how much more a contributor has to approve before contributing
+        ///
+        /// # Parameters
+        /// - `campaign_id`: ID of the campaign to contribute to
+        /// - `contributor`: Address the tokens are pulled from
+        /// - `amount`: Amount of tokens to contribute
+        ///
+        /// # Returns
+        /// Additional allowance needed in the campaign's token, `0` if the current
+        /// allowance already covers `amount`, or error if the campaign is not found
+        #[ink(message)]
+        pub fn required_allowance(
+            &self,
+            campaign_id: u32,
+            contributor: Address,
+            amount: U256,
+        ) -> Result<U256> {
+            let campaign = self.get_campaign(campaign_id)?;
+
+            // Cross-contract call to read the current allowance
+            let allowance = self
+                .token_of(&campaign)
+                .allowance(contributor, self.env().address());
+            Ok(amount.saturating_sub(allowance))
+        }
+
+        /// Get the contract's actual token balance
+        ///
+        /// Compare with `get_total_value_locked` to spot tokens sent to the
+        /// contract directly or accounting drift
+        ///
+        /// # Returns
+        /// Default token balance held by this contract
+        #[ink(message)]
+        pub fn get_contract_token_balance(&self) -> U256 {
+            self.token_contract.balance_of(self.env().address())
+        }
+
+        /// Get the total supply of the default token
+        ///
+        /// Passthrough to the token contract, for clients only holding this
+        /// contract's address
+        ///
+        /// # Returns
+        /// Total supply of the default token
+        #[ink(message)]
+        pub fn get_token_total_supply(&self) -> U256 {
+            self.token_contract.total_supply()
+        }
+
+        /// Get the total value locked in the contract
+        ///
+        /// # Returns
+        /// Default tokens held on behalf of all campaigns: contributions not yet
+        /// paid out to campaign owners or refunded. Campaigns funded in other
+        /// tokens are not included
+        #[ink(message)]
+        pub fn get_total_value_locked(&self) -> U256 {
+            self.total_raised_all
+        }
+
+        /// Point the contract at a redeployed ERC20 token (admin only)
+        ///
+        /// Only allowed while the contract holds no default tokens for any
+        /// campaign (contributions, unclaimed proceeds or pending refunds), so
+        /// no balances are stranded in the old token
+        ///
+        /// # Parameters
+        /// - `token_address`: Address of the new ERC20 token contract
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn set_token_contract(&mut self, token_address: Address) -> Result<()> {
+            self.ensure_admin()?;
+
+            if self.total_raised_all != U256::zero() {
+                return Err(Error::ActiveCampaignsExist);
+            }
+
+            self.token_contract = TokenRef::from_addr(token_address);
+            self.token_address = token_address;
+            // Cached metadata belongs to the previous token
+            self.token_metadata = None;
+
+            // Emit event
+            self.env()
+                .emit_event(TokenContractUpdated { token_address });
+
+            Ok(())
+        }
+
+        /// Withdraw tokens that don't back any campaign (admin only)
+        ///
+        /// Recovers tokens sent to the contract directly and dust left behind
+        /// after refunds. Only the contract balance exceeding the total value
+        /// locked can be withdrawn, so contributions stay untouched
+        ///
+        /// # Parameters
+        /// - `amount`: Amount of tokens to send to the admin
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn withdraw_stuck_tokens(&mut self, amount: U256) -> Result<()> {
+            let admin = self.ensure_admin()?;
+
+            // Cross-contract call to read the contract's token balance
+            let balance = self.token_contract.balance_of(self.env().address());
+            let excess = balance.saturating_sub(self.total_raised_all);
+            if amount > excess {
+                return Err(Error::InsufficientExcess);
+            }
+
+            self.token_contract.transfer(admin, amount)?;
+
+            // Emit event
+            self.env()
+                .emit_event(StuckTokensWithdrawn { to: admin, amount });
+
+            Ok(())
+        }
+
+        /// Withdraw native currency received by `contribute_native` (admin only)
+        ///
+        /// Contributions are paid out and refunded in the tokens minted for them,
+        /// so the native balance isn't owed to anyone
+        ///
+        /// # Parameters
+        /// - `amount`: Amount of native currency to send to the admin
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn withdraw_native(&mut self, amount: U256) -> Result<()> {
+            let admin = self.ensure_admin()?;
+
+            self.env()
+                .transfer(admin, amount)
+                .map_err(|_| Error::NativeTransferFailed)?;
+
+            // Emit event
+            self.env().emit_event(NativeWithdrawn { to: admin, amount });
+
+            Ok(())
+        }
+
+        /// Halt campaign creation and contributions (admin only)
+        ///
+        /// Refunds, contribution withdrawals and all readers stay available while
+        /// paused
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<()> {
+            let by = self.ensure_admin()?;
+
+            self.paused = true;
+            self.env().emit_event(Paused { by });
+
+            Ok(())
+        }
+
+        /// Resume campaign creation and contributions (admin only)
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<()> {
+            let by = self.ensure_admin()?;
+
+            self.paused = false;
+            self.env().emit_event(Unpaused { by });
+
+            Ok(())
+        }
+
+        /// Check whether the contract is paused
+        ///
+        /// # Returns
+        /// `true` if campaign creation and contributions are halted
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self.paused
+        }
+
+        /// Give up the admin role for good (admin only)
+        ///
+        /// Every admin-only message fails afterwards
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn renounce_ownership(&mut self) -> Result<()> {
+            let previous_admin = self.ensure_admin()?;
+            self.admin = None;
+            self.pending_admin = None;
+
+            // Emit event
+            self.env().emit_event(OwnershipRenounced { previous_admin });
+
+            Ok(())
+        }
+
+        /// Offer the admin role to another account (admin only)
+        ///
+        /// The role only moves once `new_admin` calls `accept_ownership`. Replaces
+        /// any previous offer
+        ///
+        /// # Parameters
+        /// - `new_admin`: Account to offer the role to
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_admin: Address) -> Result<()> {
+            let previous_admin = self.ensure_admin()?;
+            self.pending_admin = Some(new_admin);
+
+            // Emit event
+            self.env().emit_event(OwnershipTransferStarted {
+                previous_admin,
+                new_admin,
+            });
+
+            Ok(())
+        }
+
+        /// Accept the admin role offered to the caller
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn accept_ownership(&mut self) -> Result<()> {
+            let new_admin = self.env().caller();
+            if self.pending_admin != Some(new_admin) {
+                return Err(Error::NotAuthorized);
+            }
+
+            let previous_admin = self.admin.replace(new_admin);
+            self.pending_admin = None;
+
+            // Emit event
+            self.env().emit_event(OwnershipTransferred {
+                previous_admin,
+                new_admin,
+            });
+
+            Ok(())
+        }
+
+        /// Withdraw a pending admin role offer (admin only)
+        ///
+        /// # Returns
+        /// Result indicating success or failure
+        #[ink(message)]
+        pub fn cancel_ownership_transfer(&mut self) -> Result<()> {
+            self.ensure_admin()?;
+            self.pending_admin = None;
+            Ok(())
+        }
+
+        /// Get the account the admin role was offered to
+        ///
+        /// # Returns
+        /// Pending admin, `None` if there is no open offer
+        #[ink(message)]
+        pub fn pending_owner(&self) -> Option<Address> {
+            self.pending_admin
+        }
+
+        /// Get the platform admin
+        ///
+        /// # Returns
+        /// Address allowed to manage platform-wide settings, `None` once the role
+        /// was renounced
+        #[ink(message)]
+        pub fn get_admin(&self) -> Option<Address> {
+            self.admin
+        }
+
+        /// Get the platform settings
+        ///
+        /// # Returns
+        /// Settings the contract was constructed with
+        #[ink(message)]
+        pub fn get_config(&self) -> Config {
+            self.config.clone()
+        }
+
+        /// Get the version of the contract code
+        ///
+        /// # Returns
+        /// `CONTRACT_VERSION` of the deployed code
+        #[ink(message)]
+        pub fn get_version(&self) -> u32 {
+            CONTRACT_VERSION
+        }
+
+        /// Get total number of campaigns
+        ///
+        /// # Returns
+        /// Total number of campaigns created, including removed ones
+        #[ink(message)]
+        pub fn get_campaign_count(&self) -> u32 {
+            self.campaigns.len()
+        }
+
+        /// Get the number of campaigns not finalized yet
+        ///
+        /// Campaigns past their deadline count as active until they are finalized,
+        /// see `effective_status`
+        ///
+        /// # Returns
+        /// Number of active campaigns
+        #[ink(message)]
+        pub fn get_active_campaign_count(&self) -> u32 {
+            self.active_campaigns
+        }
+
+        /// Get the number of finalized campaigns
+        ///
+        /// # Returns
+        /// Number of completed campaigns, successful or not
+        #[ink(message)]
+        pub fn get_completed_campaign_count(&self) -> u32 {
+            self.completed_campaigns
+        }
+
+        /// Get the IDs of all campaigns
+        ///
+        /// # Returns
+        /// Vector of campaign IDs, for clients caching campaigns by ID. Removed
+        /// campaigns are left out
+        #[ink(message)]
+        pub fn get_all_campaign_ids(&self) -> Vec<u32> {
+            (0..self.campaigns.len())
+                .filter(|&i| self.campaigns.get(i).is_some())
+                .collect()
+        }
+
+        /// Helper function to credit a contribution whose tokens were already received
+        ///
+        /// `campaign` must be read after the external call that received the
+        /// tokens, as it is written back as is
+        ///
+        /// Returns `Overflow` before writing anything if a total would overflow
+        fn record_contribution(
+            &mut self,
+            mut campaign: Campaign,
+            payer: Address,
+            contributor: Address,
+            amount: U256,
+        ) -> Result<()> {
+            let campaign_id = campaign.id;
+
+            // Compute the new totals up front, so an overflow leaves no trace
+            let current_contribution = self
+                .contributions
+                .get((campaign_id, contributor))
+                .unwrap_or_default();
+            let new_contribution = current_contribution
+                .checked_add(amount)
+                .ok_or(Error::Overflow)?;
+            campaign.raised = campaign.raised.checked_add(amount).ok_or(Error::Overflow)?;
+            // Only default token funds count towards the total value locked
+            let total_raised_all = if campaign.token.is_none() {
+                self.total_raised_all
+                    .checked_add(amount)
+                    .ok_or(Error::Overflow)?
+            } else {
+                self.total_raised_all
+            };
+
+            // Update contributor's contribution
+            self.contributions
+                .insert((campaign_id, contributor), &new_contribution);
+            if current_contribution == U256::zero() {
+                let count = self.contributor_counts.get(campaign_id).unwrap_or_default();
+                self.contributor_counts
+                    .insert(campaign_id, &count.saturating_add(1));
+            }
+
+            // Append to the history, dropping the oldest entry once it's full
+            let mut history = self
+                .contribution_history
+                .get((campaign_id, contributor))
+                .unwrap_or_default();
+            if history.len() >= MAX_CONTRIBUTION_HISTORY {
+                history.remove(0);
+            }
+            history.push((self.env().block_timestamp(), amount));
+            self.contribution_history
+                .insert((campaign_id, contributor), &history);
+
+            // Track the campaign for the contributor on their first contribution
+            if !self.contributed_to.contains((campaign_id, contributor)) {
+                let count = self.get_contributed_campaign_count(contributor);
+                self.contributed_campaigns
+                    .insert((contributor, count), &campaign_id);
+                self.contributed_campaign_counts
+                    .insert(contributor, &count.saturating_add(1));
+                self.contributed_to
+                    .insert((campaign_id, contributor), &true);
+            }
+
+            // Update the campaign in storage
+            self.campaigns.set(campaign_id, &campaign);
+            self.total_raised_all = total_raised_all;
+
+            // Emit event
+            self.env().emit_event(ContributionMade {
+                campaign_id,
+                contributor,
+                payer,
+                amount,
+            });
+
+            if campaign.raised >= campaign.goal
+                && !self.goal_reached.get(campaign_id).unwrap_or(false)
+            {
+                self.goal_reached.insert(campaign_id, &true);
+                self.env().emit_event(GoalReached {
+                    campaign_id,
+                    raised: campaign.raised,
+                });
+            }
+
+            Ok(())
+        }
+
+        /// Helper function returning the token a campaign is funded in
+        fn token_of(&self, campaign: &Campaign) -> TokenRef {
+            match campaign.token {
+                Some(token_address) => TokenRef::from_addr(token_address),
+                None => TokenRef::from_addr(self.token_address),
+            }
+        }
+
+        /// Helper function moving `amount` tokens from `from` to `to` through the
+        /// allowance `from` granted this contract
+        ///
+        /// Allowance and balance are checked first for actionable errors
+        fn pull_tokens(
+            &self,
+            token: &mut TokenRef,
+            from: Address,
+            to: Address,
+            amount: U256,
+        ) -> Result<()> {
+            // Preflight cross-contract calls
+            if token.allowance(from, self.env().address()) < amount {
+                return Err(Error::InsufficientAllowance);
+            }
+            if token.balance_of(from) < amount {
+                return Err(Error::InsufficientTokenBalance);
+            }
+
+            token.transfer_from(from, to, amount)?;
+            Ok(())
+        }
+
+        /// Helper function crediting a `transfer_and_call` of the default token to
+        /// the campaign encoded in `data`
+        fn credit_token_transfer(&mut self, from: Address, value: U256, data: &[u8]) -> Result<()> {
+            // Only the default token vouches for a transfer having happened
+            if self.env().caller() != self.token_address {
+                return Err(Error::NotAuthorized);
+            }
+            self.ensure_not_paused()?;
+            if value == U256::zero() {
+                return Err(Error::InvalidParameters);
+            }
+
+            let campaign_id = <[u8; 4]>::try_from(data)
+                .map(u32::from_le_bytes)
+                .map_err(|_| Error::InvalidParameters)?;
+            let campaign = self.get_campaign_mut(campaign_id)?;
+            self.ensure_accepting_contributions(&campaign)?;
+            if campaign.token.is_some() {
+                return Err(Error::InvalidParameters);
+            }
+            Self::ensure_not_self_contribution(&campaign, from)?;
+
+            self.record_contribution(campaign, from, from, value)
+        }
+
+        /// Helper function sending a campaign's tokens out of the contract
+        fn pay_out(&mut self, campaign: &Campaign, to: Address, amount: U256) -> Result<()> {
+            // Only default token funds count towards the total value locked
+            if campaign.token.is_none() {
+                self.total_raised_all = self.total_raised_all.saturating_sub(amount);
+            }
+
+            self.token_of(campaign).transfer(to, amount)?;
+            Ok(())
+        }
+
+        /// Helper function to get the last timestamp at which a campaign accepts
+        /// contributions
+        ///
+        /// Contributions are accepted up to and including `deadline + grace_period`,
+        /// and the campaign can only be finalized strictly after it
+        fn contribution_end(&self, campaign: &Campaign) -> u64 {
+            campaign.deadline.saturating_add(self.config.grace_period)
+        }
+
+        /// Helper function to check that the caller may edit a campaign
+        fn ensure_editable(&self, campaign: &Campaign) -> Result<()> {
+            if self.env().caller() != campaign.owner {
+                return Err(Error::OnlyOwner);
+            }
+
+            if campaign.completed {
+                return Err(Error::CampaignCompleted);
+            }
+
+            // Backers pledged under the current terms
+            if self.config.lock_on_first_contribution && campaign.raised > U256::zero() {
+                return Err(Error::CampaignLocked);
+            }
+
+            Ok(())
+        }
+
+        /// Helper function to check that the caller is the admin
+        fn ensure_admin(&self) -> Result<Address> {
+            let caller = self.env().caller();
+            if self.admin != Some(caller) {
+                return Err(Error::NotAuthorized);
+            }
+            Ok(caller)
+        }
+
+        /// Helper function to check a campaign goal and deadline against the
+        /// platform settings
+        fn ensure_valid_goal_and_deadline(&self, goal: U256, deadline: u64) -> Result<()> {
+            let now = self.env().block_timestamp();
+            if goal == U256::zero() || goal < self.config.min_goal || deadline <= now {
+                return Err(Error::InvalidParameters);
+            }
+            if self.config.max_duration != 0
+                && deadline > now.saturating_add(self.config.max_duration)
+            {
+                return Err(Error::InvalidParameters);
+            }
+            if deadline < now.saturating_add(self.config.min_duration) {
+                return Err(Error::InvalidParameters);
+            }
+            Ok(())
+        }
+
+        /// Helper function to reject state changes blocked while paused
+        fn ensure_not_paused(&self) -> Result<()> {
+            if self.paused {
+                return Err(Error::Paused);
+            }
+            Ok(())
+        }
+
+        /// Helper function to check that a campaign still accepts contributions
+        fn ensure_accepting_contributions(&self, campaign: &Campaign) -> Result<()> {
+            // Check if deadline and grace period have passed
+            if self.env().block_timestamp() > self.contribution_end(campaign) {
+                return Err(Error::DeadlineReached);
+            }
+
+            // Check if campaign is already completed
+            if campaign.completed {
+                return Err(Error::CampaignCompleted);
+            }
+
+            Ok(())
+        }
+
+        /// Helper function rejecting contributions by the campaign owner when the
+        /// campaign forbids them
+        fn ensure_not_self_contribution(campaign: &Campaign, account: Address) -> Result<()> {
+            if !campaign.allow_self_contribution && account == campaign.owner {
+                return Err(Error::SelfContribution);
+            }
+            Ok(())
+        }
+
+        /// Helper function returning the token decimals, caching the token metadata
+        /// on first use
+        fn token_decimals(&mut self) -> u8 {
+            if self.token_metadata.is_none() {
+                self.token_metadata = Some(self.get_token_metadata());
+            }
+            self.token_metadata
+                .as_ref()
+                .map(|(_, _, decimals)| *decimals)
+                .unwrap_or_default()
+        }
+
+        /// Helper function converting whole tokens to base units
+        fn scale_whole_tokens(amount: U256, decimals: u8) -> Result<U256> {
+            U256::from(10)
+                .checked_pow(U256::from(decimals))
+                .and_then(|factor| amount.checked_mul(factor))
+                .ok_or(Error::Overflow)
+        }
+
+        /// Helper function to check whether a campaign raised enough to succeed
+        /// and wasn't made refundable by the admin
+        fn is_successful(campaign: &Campaign) -> bool {
+            campaign.raised >= campaign.soft_cap && !campaign.refunds_forced
+        }
+
+        /// Helper function to check that a campaign failed and can still be refunded
+        fn ensure_refundable(&self, campaign: &Campaign) -> Result<()> {
+            if !campaign.completed {
+                return Err(Error::CampaignCompleted);
+            }
+
+            if Self::is_successful(campaign) {
+                return Err(Error::GoalNotMet);
+            }
+
+            // Unclaimed refunds were swept after the refund window
+            if self.swept.get(campaign.id).unwrap_or(false) {
+                return Err(Error::AlreadyClaimed);
+            }
+
+            Ok(())
+        }
+
+        /// Helper function to express `part` in basis points of `total`, capped at
+        /// `BASIS_POINTS`
+        ///
+        /// Returns `0` when `total` is zero. Multiplies before dividing to keep
+        /// precision, and scales down `total` instead when that would overflow
+        fn basis_points(part: U256, total: U256) -> u32 {
+            if total == U256::zero() {
+                return 0;
+            }
+            if part >= total {
+                return BASIS_POINTS;
+            }
+
+            let scale = U256::from(BASIS_POINTS);
+            let bps = match part.checked_mul(scale) {
+                Some(scaled) => scaled / total,
+                // `part < total` and `part * scale` overflowed, so `total / scale`
+                // is far from zero
+                None => part / (total / scale),
+            };
+            bps.min(scale).low_u32()
+        }
+
+        /// Helper function to account for a contributor no longer holding a
+        /// contribution to a campaign
+        fn decrement_contributor_count(&mut self, campaign_id: u32) {
+            let count = self.contributor_counts.get(campaign_id).unwrap_or_default();
+            self.contributor_counts
+                .insert(campaign_id, &count.saturating_sub(1));
+        }
+
+        /// Helper function paying back a contributor's whole contribution to
+        /// `recipient`
+        fn refund_contribution(
+            &mut self,
+            campaign_id: u32,
+            contributor: Address,
+            recipient: Address,
+            contribution: U256,
+        ) -> Result<()> {
+            let mut campaign = self.get_campaign_mut(campaign_id)?;
+
+            // Remove contribution from mapping and the funds the campaign holds,
+            // all before transferring
+            self.contributions.remove((campaign_id, contributor));
+            self.decrement_contributor_count(campaign_id);
+            let refunded = self.total_refunded.get(campaign_id).unwrap_or_default();
+            self.total_refunded
+                .insert(campaign_id, &refunded.saturating_add(contribution));
+            campaign.raised = campaign.raised.saturating_sub(contribution);
+            self.campaigns.set(campaign_id, &campaign);
+
+            // Transfer refund to the recipient
+            self.pay_out(&campaign, recipient, contribution)?;
+
+            // Emit event
+            self.env().emit_event(RefundClaimed {
+                campaign_id,
+                contributor,
+                amount: contribution,
+            });
+
+            Ok(())
+        }
+
+        /// Helper function returning the range of campaign IDs covered by a page
+        ///
+        /// The range is clamped to existing campaigns and at most `MAX_PAGE_SIZE` long
+        fn page(&self, start: u32, limit: u32) -> core::ops::Range<u32> {
+            let len = self.campaigns.len();
+            let start = start.min(len);
+            let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(len);
+            start..end
+        }
+
+        /// Helper function computing `value * numerator / denominator` without
+        /// intermediate overflow
+        ///
+        /// Returns `0` when `denominator` is zero. Callers keep
+        /// `numerator <= denominator`, so the result always fits in `U256`
+        fn mul_div(value: U256, numerator: U256, denominator: U256) -> U256 {
+            if denominator == U256::zero() {
+                return U256::zero();
+            }
+            U256::try_from(value.full_mul(numerator) / denominator).unwrap_or(U256::MAX)
+        }
+
+        /// Helper function to validate milestone amounts and pair each with a
+        /// `released` flag
+        ///
+        /// Milestones must be non-zero and sum to exactly `goal`
+        fn build_milestones(goal: U256, amounts: Vec<U256>) -> Result<Vec<(U256, bool)>> {
+            if amounts.len() > MAX_MILESTONES {
+                return Err(Error::InvalidParameters);
+            }
+
+            let mut total = U256::zero();
+            for amount in &amounts {
+                if *amount == U256::zero() {
+                    return Err(Error::InvalidParameters);
+                }
+                total = total.checked_add(*amount).ok_or(Error::InvalidParameters)?;
+            }
+
+            if !amounts.is_empty() && total != goal {
+                return Err(Error::InvalidParameters);
+            }
+
+            Ok(amounts.into_iter().map(|amount| (amount, false)).collect())
+        }
+
+        /// Helper function to get mutable reference to campaign
+        fn get_campaign_mut(&mut self, campaign_id: u32) -> Result<Campaign> {
+            self.campaigns
+                .get(campaign_id)
+                .ok_or(Error::CampaignNotFound)
+        }
+    }
+
+    /// In-memory stand-in for `TokenRef` used by the unit tests, enabled with the
+    /// `mock-token` feature
+    ///
+    /// Off-chain, any call into a `TokenRef` panics because no contract is
+    /// deployed at its address. The mock answers the messages this contract uses
+    /// from per-thread state keyed by token address, so tests can drive the flows
+    /// moving tokens end to end. As with the real token, calls see this contract
+    /// as their caller and fail with the same errors
+    #[cfg(all(test, feature = "mock-token"))]
+    mod mock_token {
+        use super::*;
+        use ink::env::hash::Keccak256;
+        use std::{cell::RefCell, collections::BTreeMap};
+        use token::Result as TokenResult;
+
+        /// State of one mocked token
+        #[derive(Default)]
+        struct MockToken {
+            name: String,
+            symbol: String,
+            decimals: u8,
+            balances: BTreeMap<Address, U256>,
+            allowances: BTreeMap<(Address, Address), U256>,
+            nonces: BTreeMap<Address, u64>,
+            max_approval: Option<U256>,
+            owner: Option<Address>,
+            pending_owner: Option<Address>,
+            renounced: bool,
+            failing: bool,
+        }
+
+        impl MockToken {
+            fn balance(&self, owner: Address) -> U256 {
+                self.balances.get(&owner).copied().unwrap_or_default()
+            }
+
+            fn allowance(&self, owner: Address, spender: Address) -> U256 {
+                self.allowances
+                    .get(&(owner, spender))
+                    .copied()
+                    .unwrap_or_default()
+            }
+
+            fn ensure_not_failing(&self) -> TokenResult<()> {
+                if self.failing {
+                    return Err(TokenError::NotAuthorized);
+                }
+                Ok(())
+            }
+
+            fn move_tokens(&mut self, from: Address, to: Address, value: U256) -> TokenResult<()> {
+                self.ensure_not_failing()?;
+                let from_balance = self
+                    .balance(from)
+                    .checked_sub(value)
+                    .ok_or(TokenError::InsufficientBalance)?;
+                self.balances.insert(from, from_balance);
+                let to_balance = self
+                    .balance(to)
+                    .checked_add(value)
+                    .ok_or(TokenError::Overflow)?;
+                self.balances.insert(to, to_balance);
+                Ok(())
+            }
+        }
+
+        std::thread_local! {
+            static TOKENS: RefCell<BTreeMap<Address, MockToken>> =
+                const { RefCell::new(BTreeMap::new()) };
+        }
+
+        /// Runs `f` on the state of the token at `token`, creating it if needed
+        fn with_token<R>(token: Address, f: impl FnOnce(&mut MockToken) -> R) -> R {
+            TOKENS.with(|tokens| f(tokens.borrow_mut().entry(token).or_default()))
+        }
+
+        /// Sets the balance `owner` holds of the token at `token`
+        pub fn set_balance(token: Address, owner: Address, value: U256) {
+            with_token(token, |state| {
+                state.balances.insert(owner, value);
+            });
+        }
+
+        /// Sets the amount of `owner`'s tokens `spender` is allowed to move
+        pub fn set_allowance(token: Address, owner: Address, spender: Address, value: U256) {
+            with_token(token, |state| {
+                state.allowances.insert((owner, spender), value);
+            });
+        }
+
+        /// Sets the metadata reported by the token at `token`
+        pub fn set_metadata(token: Address, name: &str, symbol: &str, decimals: u8) {
+            with_token(token, |state| {
+                state.name = String::from(name);
+                state.symbol = String::from(symbol);
+                state.decimals = decimals;
+            });
+        }
+
+        /// Makes every transfer and mint of the token at `token` fail
+        pub fn set_failing(token: Address, failing: bool) {
+            with_token(token, |state| state.failing = failing);
+        }
+
+        /// Renounces ownership of the token at `token`, which freezes minting
+        pub fn renounce_ownership(token: Address) {
+            with_token(token, |state| {
+                state.owner = None;
+                state.pending_owner = None;
+                state.renounced = true;
+            });
+        }
+
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        #[ink::scale_derive(Encode, Decode, TypeInfo)]
+        #[cfg_attr(feature = "std", derive(StorageLayout))]
+        pub struct TokenRef {
+            addr: Address,
+        }
+
+        impl FromAddr for TokenRef {
+            fn from_addr(addr: Address) -> Self {
+                Self { addr }
+            }
+        }
+
+        impl ink::ToAddr for TokenRef {
+            fn to_addr(&self) -> Address {
+                self.addr
+            }
+        }
+
+        impl TokenRef {
+            #[allow(clippy::new_ret_no_self)]
+            pub fn new(
+                name: String,
+                symbol: String,
+                decimals: u8,
+                initial_supply: U256,
+                max_approval: Option<U256>,
+            ) -> TokenBuilder {
+                TokenBuilder {
+                    name,
+                    symbol,
+                    decimals,
+                    initial_supply,
+                    max_approval,
+                }
+            }
+
+            pub fn name(&self) -> String {
+                with_token(self.addr, |state| state.name.clone())
+            }
+
+            pub fn pending_owner(&self) -> Option<Address> {
+                with_token(self.addr, |state| state.pending_owner)
+            }
+
+            pub fn transfer_ownership(&mut self, new_owner: Address) -> TokenResult<()> {
+                with_token(self.addr, |state| {
+                    if state.owner != Some(ink::env::address()) {
+                        return Err(TokenError::NotAuthorized);
+                    }
+                    state.pending_owner = Some(new_owner);
+                    Ok(())
+                })
+            }
+
+            pub fn symbol(&self) -> String {
+                with_token(self.addr, |state| state.symbol.clone())
+            }
+
+            pub fn decimals(&self) -> u8 {
+                with_token(self.addr, |state| state.decimals)
+            }
+
+            pub fn address(&self) -> Address {
+                self.addr
+            }
+
+            pub fn total_supply(&self) -> U256 {
+                with_token(self.addr, |state| {
+                    state
+                        .balances
+                        .values()
+                        .fold(U256::zero(), |total, &balance| {
+                            total.saturating_add(balance)
+                        })
+                })
+            }
+
+            pub fn balance_of(&self, owner: Address) -> U256 {
+                with_token(self.addr, |state| state.balance(owner))
+            }
+
+            pub fn allowance(&self, owner: Address, spender: Address) -> U256 {
+                with_token(self.addr, |state| state.allowance(owner, spender))
+            }
+
+            pub fn transfer(&mut self, to: Address, value: U256) -> TokenResult<()> {
+                let from = ink::env::address();
+                with_token(self.addr, |state| state.move_tokens(from, to, value))
+            }
+
+            pub fn transfer_from(
+                &mut self,
+                from: Address,
+                to: Address,
+                value: U256,
+            ) -> TokenResult<()> {
+                let spender = ink::env::address();
+                with_token(self.addr, |state| {
+                    let allowance = state
+                        .allowance(from, spender)
+                        .checked_sub(value)
+                        .ok_or(TokenError::InsufficientAllowance)?;
+                    state.move_tokens(from, to, value)?;
+                    state.allowances.insert((from, spender), allowance);
+                    Ok(())
+                })
+            }
+
+            pub fn mint(&mut self, to: Address, value: U256) -> TokenResult<()> {
+                with_token(self.addr, |state| {
+                    state.ensure_not_failing()?;
+                    if state.renounced {
+                        return Err(TokenError::NotAuthorized);
+                    }
+                    let balance = state
+                        .balance(to)
+                        .checked_add(value)
+                        .ok_or(TokenError::Overflow)?;
+                    state.balances.insert(to, balance);
+                    Ok(())
+                })
+            }
+
+            pub fn nonces(&self, owner: Address) -> u64 {
+                with_token(self.addr, |state| {
+                    state.nonces.get(&owner).copied().unwrap_or_default()
+                })
+            }
+
+            /// Same hash as the real token, so signatures made for it verify here
+            pub fn permit_hash(
+                &self,
+                owner: Address,
+                spender: Address,
+                value: U256,
+                nonce: u64,
+                deadline: u64,
+            ) -> [u8; 32] {
+                let payload = (
+                    token::PERMIT_DOMAIN,
+                    self.addr,
+                    owner,
+                    spender,
+                    value,
+                    nonce,
+                    deadline,
+                );
+                let mut hash = [0u8; 32];
+                ink::env::hash_encoded::<Keccak256, _>(&payload, &mut hash);
+                hash
+            }
+
+            /// Verifies the ECDSA signature and consumes the nonce like the real
+            /// token
+            pub fn permit(
+                &mut self,
+                owner: Address,
+                spender: Address,
+                value: U256,
+                deadline: u64,
+                signature: [u8; 65],
+            ) -> TokenResult<()> {
+                if ink::env::block_timestamp::<ink::env::DefaultEnvironment>() > deadline {
+                    return Err(TokenError::PermitExpired);
+                }
+                if with_token(self.addr, |state| {
+                    state.max_approval.is_some_and(|max| value > max)
+                }) {
+                    return Err(TokenError::ApprovalTooLarge);
+                }
+
+                let nonce = self.nonces(owner);
+                let message_hash = self.permit_hash(owner, spender, value, nonce, deadline);
+                let mut public_key = [0u8; 33];
+                ink::env::ecdsa_recover(&signature, &message_hash, &mut public_key)
+                    .map_err(|_| TokenError::InvalidSignature)?;
+                let mut signer = [0u8; 20];
+                ink::env::ecdsa_to_eth_address(&public_key, &mut signer)
+                    .map_err(|_| TokenError::InvalidSignature)?;
+                if Address::from(signer) != owner {
+                    return Err(TokenError::InvalidSignature);
+                }
+
+                with_token(self.addr, |state| {
+                    state.nonces.insert(owner, nonce.saturating_add(1));
+                    state.allowances.insert((owner, spender), value);
+                });
+                Ok(())
+            }
+        }
+
+        /// Mirrors the instantiation builder of the real `TokenRef`
+        pub struct TokenBuilder {
+            name: String,
+            symbol: String,
+            decimals: u8,
+            initial_supply: U256,
+            max_approval: Option<U256>,
+        }
+
+        impl TokenBuilder {
+            pub fn code_hash(self, _code_hash: H256) -> Self {
+                self
+            }
+
+            pub fn endowment(self, _endowment: U256) -> Self {
+                self
+            }
+
+            pub fn salt_bytes(self, _salt: Option<[u8; 32]>) -> Self {
+                self
+            }
+
+            /// Deploys the mocked token at a fresh address, minting the initial
+            /// supply to this contract
+            pub fn instantiate(self) -> TokenRef {
+                let addr = TOKENS.with(|tokens| {
+                    let mut bytes = [0xee; 20];
+                    bytes[..4].copy_from_slice(&(tokens.borrow().len() as u32).to_be_bytes());
+                    Address::from(bytes)
+                });
+                with_token(addr, |state| {
+                    state.name = self.name;
+                    state.symbol = self.symbol;
+                    state.decimals = self.decimals.min(token::MAX_DECIMALS);
+                    state.max_approval = self.max_approval;
+                    state.owner = Some(ink::env::address());
+                    state
+                        .balances
+                        .insert(ink::env::address(), self.initial_supply);
+                });
+                TokenRef { addr }
+            }
+        }
+    }
+
+    /// Unit tests that don't call the token, run with and without the
+    /// `mock-token` feature
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        pub(super) fn set_caller(sender: Address) {
+            ink::env::test::set_caller(sender);
+        }
+
+        pub(super) fn set_block_timestamp(timestamp: u64) {
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(timestamp);
+        }
+
+        /// Creates a campaign titled "Test Campaign" as the current caller and
+        /// returns its ID
+        pub(super) fn create_test_campaign(
+            contract: &mut InkFundMe,
+            goal: u128,
+            deadline: u64,
+            options: CampaignOptions,
+        ) -> u32 {
+            contract
+                .create_campaign(
+                    String::from("Test Campaign"),
+                    String::from("Description"),
+                    U256::from(goal),
+                    deadline,
+                    options,
+                )
+                .unwrap()
+                .id
+        }
+
+        /// Marks a campaign as having raised `raised` without going through the token
+        pub(super) fn set_raised(contract: &mut InkFundMe, campaign_id: u32, raised: U256) {
+            let mut campaign = contract.get_campaign(campaign_id).unwrap();
+            campaign.raised = raised;
+            contract.campaigns.set(campaign_id, &campaign);
+        }
+
+        #[ink::test]
+        fn create_campaign_works() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+
+            // Set a current timestamp
+            set_block_timestamp(500000000);
+
+            let title = String::from("Test Campaign");
+            let description = String::from("A test campaign");
+            let goal = U256::from(1000);
+            let deadline = 1000000000; // Future timestamp
+
+            let result = contract.create_campaign(
+                title.clone(),
+                description.clone(),
+                goal,
+                deadline,
+                CampaignOptions::default(),
+            );
+            assert!(result.is_ok());
+
+            let created = result.unwrap();
+            let campaign_id = created.id;
+            assert_eq!(campaign_id, 0);
+            assert_eq!(contract.get_campaign_count(), 1);
+
+            let campaign = contract.get_campaign(campaign_id).unwrap();
+            assert_eq!(campaign.title, title);
+            assert_eq!(campaign.description, description);
+            assert_eq!(campaign.goal, goal);
+            assert_eq!(campaign.deadline, deadline);
+            assert_eq!(campaign.raised, U256::zero());
+            assert!(!campaign.completed);
+            assert_eq!(campaign.created_at, 500000000);
+            assert_eq!(campaign, created);
+        }
+
+        #[ink::test]
+        fn create_campaign_invalid_parameters() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+
+            // Set a current timestamp
+            set_block_timestamp(500000000);
+
+            // Test with zero goal
+            let result = contract.create_campaign(
+                String::from("Test"),
+                String::from("Test"),
+                U256::zero(),
+                1000000000,
+                CampaignOptions::default(),
+            );
+            assert_eq!(result, Err(Error::InvalidParameters));
+
+            // Test with past deadline
+            let result = contract.create_campaign(
+                String::from("Test"),
+                String::from("Test"),
+                U256::from(1000),
+                0, // Past timestamp
+                CampaignOptions::default(),
+            );
+            assert_eq!(result, Err(Error::InvalidParameters));
+        }
+
+        #[ink::test]
+        fn get_campaign_not_found() {
+            let token_address = Address::from([0x42; 20]);
+            let contract = InkFundMe::new(token_address, Config::default());
+            let result = contract.get_campaign(0);
+            assert_eq!(result, Err(Error::CampaignNotFound));
+        }
+
+        #[ink::test]
+        fn get_contribution_works() {
+            let token_address = Address::from([0x42; 20]);
+            let contract = InkFundMe::new(token_address, Config::default());
+            let contributor = Address::from([0x01; 20]);
+            let contribution = contract.get_contribution(0, contributor);
+            assert_eq!(contribution, U256::zero());
+        }
+
+        #[ink::test]
+        fn get_all_campaigns_works() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+
+            // Initially no campaigns
+            let campaigns = contract.get_all_campaigns();
+            assert_eq!(campaigns.len(), 0);
+
+            // Set timestamp for campaign creation
+            set_block_timestamp(500000000);
+
+            // Create a campaign
+            let result = contract.create_campaign(
+                String::from("Test Campaign"),
+                String::from("Description"),
+                U256::from(1000),
+                1000000000,
+                CampaignOptions::default(),
+            );
+            assert!(result.is_ok());
+
+            // Now should have one campaign
+            let campaigns = contract.get_all_campaigns();
+            assert_eq!(campaigns.len(), 1);
+            assert_eq!(campaigns[0].title, "Test Campaign");
+        }
+
+        #[ink::test]
+        fn multiple_campaigns_work() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+
+            // Set timestamp
+            set_block_timestamp(500000000);
+
+            // Create multiple campaigns
+            for i in 0..3 {
+                let result = contract.create_campaign(
+                    format!("Campaign {}", i),
+                    format!("Description {}", i),
+                    U256::from(1000 + i as u128),
+                    1000000000 + i as u64,
+                    CampaignOptions::default(),
+                );
+                assert!(result.is_ok());
+                assert_eq!(result.unwrap().id, i as u32);
+            }
+
+            assert_eq!(contract.get_campaign_count(), 3);
+            assert_eq!(contract.get_all_campaign_ids(), vec![0, 1, 2]);
+
+            // Check each campaign
+            for i in 0..3 {
+                let campaign = contract.get_campaign(i as u32).unwrap();
+                assert_eq!(campaign.title, format!("Campaign {}", i));
+                assert_eq!(campaign.goal, U256::from(1000 + i as u128));
+            }
+        }
+
+        #[ink::test]
+        fn active_and_completed_counts_work() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            assert_eq!(contract.get_active_campaign_count(), 0);
+            assert_eq!(contract.get_completed_campaign_count(), 0);
+
+            for deadline in [1000000000, 2000000000, 2000000000] {
+                create_test_campaign(&mut contract, 1000, deadline, CampaignOptions::default());
+            }
+            assert_eq!(contract.get_active_campaign_count(), 3);
+            assert_eq!(contract.get_completed_campaign_count(), 0);
+
+            set_block_timestamp(1000000001);
+            assert!(contract.finalize(0).is_ok());
+            assert_eq!(contract.get_active_campaign_count(), 2);
+            assert_eq!(contract.get_completed_campaign_count(), 1);
+
+            // Failed attempts leave the counts alone
+            assert_eq!(contract.finalize(0), Err(Error::AlreadyFinalized));
+            assert_eq!(contract.finalize(1), Err(Error::DeadlineNotReached));
+            assert_eq!(contract.get_active_campaign_count(), 2);
+            assert_eq!(contract.get_completed_campaign_count(), 1);
+            assert_eq!(contract.get_campaign_count(), 3);
+        }
+
+        #[ink::test]
+        fn get_campaigns_expiring_before_works() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            for deadline in [1000000000, 3000000000, 2000000000, 1500000000] {
+                create_test_campaign(&mut contract, 1000, deadline, CampaignOptions::default());
+            }
+
+            assert_eq!(
+                contract.get_campaigns_expiring_before(2000000000, 0, 10),
+                vec![0, 3]
+            );
+            // The cutoff itself is excluded
+            assert_eq!(
+                contract.get_campaigns_expiring_before(2000000001, 0, 10),
+                vec![0, 2, 3]
+            );
+            assert!(
+                contract
+                    .get_campaigns_expiring_before(1000000000, 0, 10)
+                    .is_empty()
+            );
+            // Only the window is scanned
+            assert_eq!(
+                contract.get_campaigns_expiring_before(2000000000, 1, 2),
+                Vec::<u32>::new()
+            );
+
+            // Finalized campaigns need no reminder
+            set_block_timestamp(1000000001);
+            assert!(contract.finalize(0).is_ok());
+            assert_eq!(
+                contract.get_campaigns_expiring_before(2000000000, 0, 10),
+                vec![3]
+            );
+        }
+
+        #[ink::test]
+        fn find_campaigns_by_title_prefix_works() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            for title in [
+                "Save the Park",
+                "Save the River",
+                "Build a School",
+                "save the Zoo",
+            ] {
+                contract
+                    .create_campaign(
+                        String::from(title),
+                        String::from("Description"),
+                        U256::from(1000),
+                        1000000000,
+                        CampaignOptions::default(),
+                    )
+                    .unwrap();
+            }
+
+            let find = |prefix: &str, start, limit| {
+                contract.find_campaigns_by_title_prefix(String::from(prefix), start, limit)
+            };
+            assert_eq!(find("Save the", 0, 10), vec![0, 1]);
+            assert_eq!(find("Save the R", 0, 10), vec![1]);
+            assert_eq!(find("Build", 0, 10), vec![2]);
+            // Case-sensitive
+            assert_eq!(find("save", 0, 10), vec![3]);
+            // Only the window is scanned
+            assert_eq!(find("Save the", 1, 2), vec![1]);
+            assert_eq!(find("", 2, 10), vec![2, 3]);
+            assert!(find("Nothing", 0, 10).is_empty());
+
+            let too_long = "a".repeat(MAX_SEARCH_PREFIX_LEN + 1);
+            assert!(find(&too_long, 0, 10).is_empty());
+        }
+
+        #[ink::test]
+        fn get_campaigns_sorted_by_newest_works() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            assert!(contract.get_campaigns_sorted_by_newest(0, 10).is_empty());
+
+            for i in 0..3 {
+                set_block_timestamp(500000000 + i);
+                contract
+                    .create_campaign(
+                        format!("Campaign {}", i),
+                        String::from("Description"),
+                        U256::from(1000),
+                        1000000000,
+                        CampaignOptions::default(),
+                    )
+                    .unwrap();
+            }
+
+            let created_at = |campaigns: Vec<Campaign>| {
+                campaigns
+                    .iter()
+                    .map(|campaign| campaign.created_at)
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(
+                created_at(contract.get_campaigns_sorted_by_newest(0, 10)),
+                vec![500000002, 500000001, 500000000]
+            );
+            assert_eq!(
+                created_at(contract.get_campaigns_sorted_by_newest(1, 1)),
+                vec![500000001]
+            );
+            assert!(contract.get_campaigns_sorted_by_newest(3, 10).is_empty());
+        }
+
+        #[ink::test]
+        fn create_campaign_with_milestones_works() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            let campaign_id = create_test_campaign(
+                &mut contract,
+                1000,
+                1000000000,
+                CampaignOptions {
+                    milestones: vec![U256::from(400), U256::from(600)],
+                    ..CampaignOptions::default()
+                },
+            );
+
+            let campaign = contract.get_campaign(campaign_id).unwrap();
+            assert_eq!(
+                campaign.milestones,
+                vec![(U256::from(400), false), (U256::from(600), false)]
+            );
+        }
+
+        #[ink::test]
+        fn create_campaign_milestones_must_sum_to_goal() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            let result = contract.create_campaign(
+                String::from("Test Campaign"),
+                String::from("Description"),
+                U256::from(1000),
+                1000000000,
+                CampaignOptions {
+                    milestones: vec![U256::from(400), U256::from(500)],
+                    ..CampaignOptions::default()
+                },
+            );
+            assert_eq!(result, Err(Error::InvalidParameters));
+
+            // Zero-sized tranches are rejected as well
+            let result = contract.create_campaign(
+                String::from("Test Campaign"),
+                String::from("Description"),
+                U256::from(1000),
+                1000000000,
+                CampaignOptions {
+                    milestones: vec![U256::from(1000), U256::zero()],
+                    ..CampaignOptions::default()
+                },
+            );
+            assert_eq!(result, Err(Error::InvalidParameters));
+        }
+
+        #[ink::test]
+        fn get_campaign_progress_works() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            // 0%
+            assert_eq!(contract.get_campaign_progress(campaign_id), Ok(0));
+
+            // 50%
+            set_raised(&mut contract, campaign_id, U256::from(500));
+            assert_eq!(contract.get_campaign_progress(campaign_id), Ok(5000));
+
+            // 100%
+            set_raised(&mut contract, campaign_id, U256::from(1000));
+            assert_eq!(contract.get_campaign_progress(campaign_id), Ok(10000));
+
+            // Over 100% is capped
+            set_raised(&mut contract, campaign_id, U256::from(2500));
+            assert_eq!(contract.get_campaign_progress(campaign_id), Ok(10000));
+
+            assert_eq!(
+                contract.get_campaign_progress(1),
+                Err(Error::CampaignNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn get_campaign_raised_and_goal_work() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            assert_eq!(contract.get_campaign_raised(campaign_id), Ok(U256::zero()));
+            assert_eq!(
+                contract.get_campaign_goal(campaign_id),
+                Ok(U256::from(1000))
+            );
+
+            let contributor = Address::from([0x02; 20]);
+            let campaign = contract.get_campaign(campaign_id).unwrap();
+            contract
+                .record_contribution(campaign, contributor, contributor, U256::from(300))
+                .unwrap();
+            assert_eq!(
+                contract.get_campaign_raised(campaign_id),
+                Ok(U256::from(300))
+            );
+            assert_eq!(
+                contract.get_campaign_goal(campaign_id),
+                Ok(U256::from(1000))
+            );
+
+            assert_eq!(
+                contract.get_campaign_raised(1),
+                Err(Error::CampaignNotFound)
+            );
+            assert_eq!(contract.get_campaign_goal(1), Err(Error::CampaignNotFound));
+        }
+
+        #[ink::test]
+        fn basis_points_handles_large_values() {
+            // `part * 10000` overflows U256 but the ratio is still 50%
+            let total = U256::MAX - U256::from(1);
+            let part = total / 2;
+            assert_eq!(InkFundMe::basis_points(part, total), 5000);
+
+            assert_eq!(InkFundMe::basis_points(U256::MAX, U256::MAX), 10000);
+            assert_eq!(InkFundMe::basis_points(U256::from(1), U256::zero()), 0);
+        }
+
+        #[ink::test]
+        fn time_remaining_and_is_expired_work() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            let deadline = 1000000000;
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, deadline, CampaignOptions::default());
+
+            // Before the deadline
+            assert_eq!(contract.time_remaining(campaign_id), Ok(500000000));
+            assert_eq!(contract.is_expired(campaign_id), Ok(false));
+
+            // At the deadline
+            set_block_timestamp(deadline);
+            assert_eq!(contract.time_remaining(campaign_id), Ok(0));
+            assert_eq!(contract.is_expired(campaign_id), Ok(false));
+
+            // After the deadline
+            set_block_timestamp(deadline + 1);
+            assert_eq!(contract.time_remaining(campaign_id), Ok(0));
+            assert_eq!(contract.is_expired(campaign_id), Ok(true));
+
+            assert_eq!(contract.time_remaining(1), Err(Error::CampaignNotFound));
+            assert_eq!(contract.is_expired(1), Err(Error::CampaignNotFound));
+        }
+
+        #[ink::test]
+        fn effective_status_works() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            let deadline = 1000000000;
+            let mut create =
+                || create_test_campaign(&mut contract, 1000, deadline, CampaignOptions::default());
+            let failed_id = create();
+            let succeeded_id = create();
+            set_raised(&mut contract, succeeded_id, U256::from(1000));
+
+            // Active until the deadline
+            assert_eq!(
+                contract.effective_status(failed_id),
+                Ok(CampaignStatus::Active)
+            );
+            set_block_timestamp(deadline);
+            assert_eq!(
+                contract.effective_status(failed_id),
+                Ok(CampaignStatus::Active)
+            );
+
+            // Expired but not finalized yet
+            set_block_timestamp(deadline + 1);
+            assert_eq!(
+                contract.effective_status(failed_id),
+                Ok(CampaignStatus::Expired)
+            );
+
+            // Finalized
+            assert!(contract.finalize(failed_id).is_ok());
+            assert!(contract.finalize(succeeded_id).is_ok());
+            assert_eq!(
+                contract.effective_status(failed_id),
+                Ok(CampaignStatus::Failed)
+            );
+            assert_eq!(
+                contract.effective_status(succeeded_id),
+                Ok(CampaignStatus::Succeeded)
+            );
+
+            assert_eq!(contract.effective_status(2), Err(Error::CampaignNotFound));
+        }
+
+        #[ink::test]
+        fn remaining_to_goal_and_overfunded_amount_work() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            // Underfunded
+            set_raised(&mut contract, campaign_id, U256::from(400));
+            assert_eq!(contract.remaining_to_goal(campaign_id), Ok(U256::from(600)));
+            assert_eq!(contract.overfunded_amount(campaign_id), Ok(U256::zero()));
+
+            // Exactly funded
+            set_raised(&mut contract, campaign_id, U256::from(1000));
+            assert_eq!(contract.remaining_to_goal(campaign_id), Ok(U256::zero()));
+            assert_eq!(contract.overfunded_amount(campaign_id), Ok(U256::zero()));
+
+            // Overfunded
+            set_raised(&mut contract, campaign_id, U256::from(1250));
+            assert_eq!(contract.remaining_to_goal(campaign_id), Ok(U256::zero()));
+            assert_eq!(contract.overfunded_amount(campaign_id), Ok(U256::from(250)));
+
+            assert_eq!(contract.remaining_to_goal(1), Err(Error::CampaignNotFound));
+            assert_eq!(contract.overfunded_amount(1), Err(Error::CampaignNotFound));
+        }
+
+        #[ink::test]
+        fn total_value_locked_tracks_contributions() {
+            let owner = Address::from([0x01; 20]);
+            let contributor = Address::from([0x02; 20]);
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_caller(owner);
+            set_block_timestamp(500000000);
+
+            let first_id = contract
+                .create_campaign(
+                    String::from("First"),
+                    String::from("Description"),
+                    U256::from(1000),
+                    1000000000,
+                    CampaignOptions::default(),
+                )
+                .unwrap()
+                .id;
+            let second_id = contract
+                .create_campaign(
+                    String::from("Second"),
+                    String::from("Description"),
+                    U256::from(500),
+                    1000000000,
+                    CampaignOptions {
+                        milestones: vec![U256::from(500)],
+                        ..CampaignOptions::default()
+                    },
+                )
+                .unwrap()
+                .id;
+            assert_eq!(contract.get_total_value_locked(), U256::zero());
+
+            // Contributions across both campaigns
+            let campaign = contract.get_campaign(first_id).unwrap();
+            contract
+                .record_contribution(campaign, contributor, contributor, U256::from(300))
+                .unwrap();
+            assert_eq!(contract.get_total_value_locked(), U256::from(300));
+
+            let campaign = contract.get_campaign(second_id).unwrap();
+            contract
+                .record_contribution(campaign, contributor, contributor, U256::from(500))
+                .unwrap();
+            assert_eq!(contract.get_total_value_locked(), U256::from(800));
+
+            // Finalizing keeps funds locked: the failed campaign holds them for
+            // refunds, the milestone campaign until tranches are released
+            set_block_timestamp(1000000001);
+            assert!(contract.finalize(first_id).is_ok());
+            assert!(contract.finalize(second_id).is_ok());
+            assert_eq!(contract.get_total_value_locked(), U256::from(800));
+        }
+
+        #[ink::test]
+        fn campaigns_can_use_their_own_token() {
+            let owner = Address::from([0x01; 20]);
+            let contributor = Address::from([0x02; 20]);
+            let token_address = Address::from([0x42; 20]);
+            let other_token = Address::from([0x43; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_caller(owner);
+            set_block_timestamp(500000000);
+
+            let default_id = contract
+                .create_campaign(
+                    String::from("Default"),
+                    String::from("Description"),
+                    U256::from(1000),
+                    1000000000,
+                    CampaignOptions::default(),
+                )
+                .unwrap()
+                .id;
+            let custom_id = contract
+                .create_campaign(
+                    String::from("Custom"),
+                    String::from("Description"),
+                    U256::from(1000),
+                    1000000000,
+                    CampaignOptions {
+                        token: Some(other_token),
+                        ..CampaignOptions::default()
+                    },
+                )
+                .unwrap()
+                .id;
+
+            assert_eq!(contract.get_campaign_token(default_id), Ok(token_address));
+            assert_eq!(contract.get_campaign_token(custom_id), Ok(other_token));
+            assert_eq!(contract.get_campaign_token(2), Err(Error::CampaignNotFound));
+
+            // Only default token contributions count towards the value locked
+            let campaign = contract.get_campaign(default_id).unwrap();
+            contract
+                .record_contribution(campaign, contributor, contributor, U256::from(300))
+                .unwrap();
+            let campaign = contract.get_campaign(custom_id).unwrap();
+            contract
+                .record_contribution(campaign, contributor, contributor, U256::from(500))
+                .unwrap();
+
+            assert_eq!(contract.get_total_value_locked(), U256::from(300));
+            assert_eq!(
+                contract.get_campaign(custom_id).unwrap().raised,
+                U256::from(500)
+            );
+        }
+
+        #[ink::test]
+        fn set_token_contract_works() {
+            let admin = Address::from([0x01; 20]);
+            let new_token = Address::from([0x43; 20]);
+            let token_address = Address::from([0x42; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            assert_eq!(contract.get_admin(), Some(admin));
+
+            // A campaign without funds doesn't block the change
+            create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            assert!(contract.set_token_contract(new_token).is_ok());
+            assert_eq!(ink::ToAddr::to_addr(&contract.token_contract), new_token);
+            assert_eq!(contract.get_token_address(), new_token);
+        }
+
+        #[ink::test]
+        fn get_version_works() {
+            let token_address = Address::from([0x42; 20]);
+            let contract = InkFundMe::new(token_address, Config::default());
+
+            assert_eq!(contract.get_version(), CONTRACT_VERSION);
+            assert_eq!(contract.schema_version, CONTRACT_VERSION);
+        }
+
+        #[ink::test]
+        fn get_token_address_is_cached() {
+            let token_address = Address::from([0x42; 20]);
+            let contract = InkFundMe::new(token_address, Config::default());
+
+            assert_eq!(contract.get_token_address(), token_address);
+            assert_eq!(
+                ink::ToAddr::to_addr(&contract.token_contract),
+                token_address
+            );
+        }
+
+        #[ink::test]
+        fn set_token_contract_non_admin_fails() {
+            let token_address = Address::from([0x42; 20]);
+            set_caller(Address::from([0x01; 20]));
+            let mut contract = InkFundMe::new(token_address, Config::default());
+
+            set_caller(Address::from([0x02; 20]));
+            assert_eq!(
+                contract.set_token_contract(Address::from([0x43; 20])),
+                Err(Error::NotAuthorized)
+            );
+        }
+
+        #[ink::test]
+        fn set_token_contract_with_active_campaign_fails() {
+            let admin = Address::from([0x01; 20]);
+            let token_address = Address::from([0x42; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            let campaign = contract.get_campaign(campaign_id).unwrap();
+            contract
+                .record_contribution(campaign, admin, admin, U256::from(100))
+                .unwrap();
+
+            assert_eq!(
+                contract.set_token_contract(Address::from([0x43; 20])),
+                Err(Error::ActiveCampaignsExist)
+            );
+            assert_eq!(
+                ink::ToAddr::to_addr(&contract.token_contract),
+                token_address
+            );
+        }
+
+        #[ink::test]
+        fn campaign_created_event_includes_title_hash() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
 
             let title = String::from("Test Campaign");
-            let description = String::from("A test campaign");
-            let goal = U256::from(1000);
-            let deadline = 1000000000; // Future timestamp
+            contract
+                .create_campaign(
+                    title.clone(),
+                    String::from("Description"),
+                    U256::from(1000),
+                    1000000000,
+                    CampaignOptions::default(),
+                )
+                .unwrap();
+
+            let emitted_events: Vec<_> = ink::env::test::recorded_events().into_iter().collect();
+            assert_eq!(emitted_events.len(), 1);
+            let event =
+                <CampaignCreated as ink::scale::Decode>::decode(&mut &emitted_events[0].data[..])
+                    .unwrap();
+
+            let mut expected = [0u8; 32];
+            ink::env::hash_bytes::<Blake2x256>(title.as_bytes(), &mut expected);
+            assert_eq!(event.title_hash, expected);
+            assert_eq!(event.id, 0);
+            assert_eq!(event.goal, U256::from(1000));
+        }
+
+        #[ink::test]
+        fn campaign_owner_readers_work() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            let other = Address::from([0x02; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            assert_eq!(contract.get_campaign_owner(campaign_id), Ok(owner));
+            assert_eq!(contract.is_campaign_owner(campaign_id, owner), Ok(true));
+            assert_eq!(contract.is_campaign_owner(campaign_id, other), Ok(false));
+
+            assert_eq!(contract.get_campaign_owner(1), Err(Error::CampaignNotFound));
+            assert_eq!(
+                contract.is_campaign_owner(1, owner),
+                Err(Error::CampaignNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn max_duration_boundaries() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(
+                token_address,
+                Config {
+                    max_duration: 1000,
+                    ..Config::default()
+                },
+            );
+            let now = 500000000;
+            set_block_timestamp(now);
+
+            let mut create = |deadline| {
+                contract.create_campaign(
+                    String::from("Test Campaign"),
+                    String::from("Description"),
+                    U256::from(1000),
+                    deadline,
+                    CampaignOptions::default(),
+                )
+            };
+
+            // Within range and exactly at the boundary
+            assert!(create(now + 500).is_ok());
+            assert!(create(now + 1000).is_ok());
+            // Beyond the boundary
+            assert_eq!(create(now + 1001), Err(Error::InvalidParameters));
+        }
+
+        #[ink::test]
+        fn min_duration_boundaries() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(
+                token_address,
+                Config {
+                    min_duration: 1000,
+                    ..Config::default()
+                },
+            );
+            let now = 500000000;
+            set_block_timestamp(now);
+
+            let mut create = |deadline| {
+                contract.create_campaign(
+                    String::from("Test Campaign"),
+                    String::from("Description"),
+                    U256::from(1000),
+                    deadline,
+                    CampaignOptions::default(),
+                )
+            };
+
+            // Below the boundary
+            assert_eq!(create(now + 1), Err(Error::InvalidParameters));
+            assert_eq!(create(now + 999), Err(Error::InvalidParameters));
+            // Exactly at the boundary and beyond
+            assert!(create(now + 1000).is_ok());
+            assert!(create(now + 5000).is_ok());
+        }
+
+        #[ink::test]
+        fn zero_max_duration_is_unlimited() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            let result = contract.create_campaign(
+                String::from("Test Campaign"),
+                String::from("Description"),
+                U256::from(1000),
+                u64::MAX,
+                CampaignOptions::default(),
+            );
+            assert!(result.is_ok());
+        }
+
+        #[ink::test]
+        fn min_goal_boundaries() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(
+                token_address,
+                Config {
+                    min_goal: U256::from(1000),
+                    ..Config::default()
+                },
+            );
+            set_block_timestamp(500000000);
+
+            let mut create = |goal: u128| {
+                contract.create_campaign(
+                    String::from("Test Campaign"),
+                    String::from("Description"),
+                    U256::from(goal),
+                    1000000000,
+                    CampaignOptions::default(),
+                )
+            };
+
+            // Exactly at the minimum
+            assert!(create(1000).is_ok());
+            // Just below
+            assert_eq!(create(999), Err(Error::InvalidParameters));
+        }
+
+        #[ink::test]
+        fn get_campaign_summaries_works() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            for i in 0..3 {
+                contract
+                    .create_campaign(
+                        format!("Campaign {}", i),
+                        "A very long description. ".repeat(100),
+                        U256::from(1000 + i as u128),
+                        1000000000 + i as u64,
+                        CampaignOptions::default(),
+                    )
+                    .unwrap();
+            }
+            set_raised(&mut contract, 1, U256::from(250));
+
+            let summaries = contract.get_campaign_summaries(1, 5);
+            assert_eq!(
+                summaries,
+                vec![
+                    CampaignSummary {
+                        id: 1,
+                        goal: U256::from(1001),
+                        raised: U256::from(250),
+                        deadline: 1000000001,
+                        completed: false,
+                    },
+                    CampaignSummary {
+                        id: 2,
+                        goal: U256::from(1002),
+                        raised: U256::zero(),
+                        deadline: 1000000002,
+                        completed: false,
+                    },
+                ]
+            );
+
+            // Summaries encode much smaller than full campaigns
+            let full = ink::scale::Encode::encoded_size(&contract.get_all_campaigns()[1..]);
+            let compact = ink::scale::Encode::encoded_size(&summaries);
+            assert!(compact < full / 10);
+
+            assert!(contract.get_campaign_summaries(3, 5).is_empty());
+        }
+
+        #[test]
+        fn error_codes_are_stable() {
+            let codes = [
+                (Error::CampaignNotFound, 1),
+                (Error::DeadlineNotReached, 2),
+                (Error::DeadlineReached, 3),
+                (Error::CampaignCompleted, 4),
+                (Error::GoalNotMet, 5),
+                (Error::NoContribution, 6),
+                (Error::OnlyOwner, 7),
+                (Error::InvalidParameters, 8),
+                (Error::NotFinalized, 9),
+                (Error::MilestoneAlreadyReleased, 10),
+                (Error::NotAuthorized, 11),
+                (Error::ActiveCampaignsExist, 12),
+                (Error::AlreadyClaimed, 13),
+                (Error::FaucetCooldown, 14),
+                (Error::NothingToContribute, 15),
+                (Error::InsufficientExcess, 16),
+                (Error::Overflow, 17),
+                (Error::Paused, 18),
+                (Error::CampaignLocked, 19),
+                (Error::AlreadyFinalized, 20),
+                (Error::InsufficientAllowance, 21),
+                (Error::InsufficientTokenBalance, 22),
+                (Error::SlippageExceeded, 23),
+                (Error::SelfContribution, 24),
+                (Error::CampaignNotEmpty, 25),
+                (Error::NativeTransferFailed, 26),
+                (Error::TokenError(TokenError::InsufficientBalance), 1001),
+                (Error::TokenError(TokenError::InsufficientAllowance), 1002),
+                (Error::TokenError(TokenError::InvalidMetadata), 1008),
+            ];
+            for (error, code) in codes {
+                assert_eq!(error.code(), code, "{:?}", error);
+            }
+        }
+
+        #[ink::test]
+        fn create_campaign_id_overflow_fails() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            contract.next_campaign_id = u32::MAX;
+
+            let result = contract.create_campaign(
+                String::from("Test Campaign"),
+                String::from("Description"),
+                U256::from(1000),
+                1000000000,
+                CampaignOptions::default(),
+            );
+            assert_eq!(result, Err(Error::Overflow));
+            assert_eq!(contract.next_campaign_id, u32::MAX);
+            assert_eq!(contract.get_campaign_count(), 0);
+        }
+
+        #[ink::test]
+        fn top_campaigns_by_raised_works() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            let raised = [300, 700, 100, 700, 500];
+            for (i, amount) in raised.into_iter().enumerate() {
+                contract
+                    .create_campaign(
+                        format!("Campaign {}", i),
+                        String::from("Description"),
+                        U256::from(1000),
+                        1000000000,
+                        CampaignOptions::default(),
+                    )
+                    .unwrap();
+                set_raised(&mut contract, i as u32, U256::from(amount));
+            }
+
+            let ids = |campaigns: Vec<Campaign>| campaigns.iter().map(|c| c.id).collect::<Vec<_>>();
+
+            // Ties keep the lower ID first
+            assert_eq!(ids(contract.top_campaigns_by_raised(3)), vec![1, 3, 4]);
+            assert_eq!(
+                ids(contract.top_campaigns_by_raised(10)),
+                vec![1, 3, 4, 0, 2]
+            );
+            assert!(contract.top_campaigns_by_raised(0).is_empty());
+        }
+
+        #[ink::test]
+        fn pause_non_admin_fails() {
+            let token_address = Address::from([0x42; 20]);
+            set_caller(Address::from([0x01; 20]));
+            let mut contract = InkFundMe::new(token_address, Config::default());
+
+            set_caller(Address::from([0x02; 20]));
+            assert_eq!(contract.pause(), Err(Error::NotAuthorized));
+            assert_eq!(contract.unpause(), Err(Error::NotAuthorized));
+            assert!(!contract.is_paused());
+        }
+
+        #[ink::test]
+        fn contribution_share_works() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            let first = Address::from([0x02; 20]);
+            let second = Address::from([0x03; 20]);
+
+            // Nothing raised yet
+            assert_eq!(contract.contribution_share(campaign_id, first), Ok(0));
+
+            for (contributor, amount) in [(first, 300), (second, 700)] {
+                let campaign = contract.get_campaign(campaign_id).unwrap();
+                contract
+                    .record_contribution(campaign, contributor, contributor, U256::from(amount))
+                    .unwrap();
+            }
+
+            assert_eq!(contract.contribution_share(campaign_id, first), Ok(3000));
+            assert_eq!(contract.contribution_share(campaign_id, second), Ok(7000));
+            assert_eq!(
+                contract.contribution_share(campaign_id, Address::from([0x04; 20])),
+                Ok(0)
+            );
+            assert_eq!(
+                contract.contribution_share(1, first),
+                Err(Error::CampaignNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn goal_reached_event_fires_once() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            let contributor = Address::from([0x02; 20]);
+
+            let mut contribute = |amount: u128| {
+                let campaign = contract.get_campaign(campaign_id).unwrap();
+                contract
+                    .record_contribution(campaign, contributor, contributor, U256::from(amount))
+                    .unwrap();
+                ink::env::test::recorded_events().into_iter().count()
+            };
+
+            // CampaignCreated + ContributionMade
+            assert_eq!(contribute(600), 2);
+            // ContributionMade + GoalReached crossing the goal
+            assert_eq!(contribute(500), 4);
+            // Only ContributionMade afterwards
+            assert_eq!(contribute(100), 5);
+
+            let event = ink::env::test::recorded_events()
+                .into_iter()
+                .nth(3)
+                .unwrap();
+            let event = <GoalReached as ink::scale::Decode>::decode(&mut &event.data[..]).unwrap();
+            assert_eq!(event.campaign_id, campaign_id);
+            assert_eq!(event.raised, U256::from(1100));
+        }
+
+        #[ink::test]
+        fn get_contributed_campaign_ids_works() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            for _ in 0..3 {
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            }
+            let contributor = Address::from([0x02; 20]);
+            assert!(
+                contract
+                    .get_contributed_campaign_ids(contributor, 0, MAX_PAGE_SIZE)
+                    .is_empty()
+            );
+
+            // Two contributions to campaign 2, one to campaign 0
+            for campaign_id in [2, 0, 2] {
+                let campaign = contract.get_campaign(campaign_id).unwrap();
+                contract
+                    .record_contribution(campaign, contributor, contributor, U256::from(100))
+                    .unwrap();
+            }
+
+            assert_eq!(
+                contract.get_contributed_campaign_ids(contributor, 0, MAX_PAGE_SIZE),
+                vec![2, 0]
+            );
+            assert_eq!(contract.get_contributed_campaign_count(contributor), 2);
+            assert_eq!(contract.get_contribution(2, contributor), U256::from(200));
+
+            // Paged
+            assert_eq!(
+                contract.get_contributed_campaign_ids(contributor, 1, 1),
+                vec![0]
+            );
+            assert!(
+                contract
+                    .get_contributed_campaign_ids(contributor, 2, MAX_PAGE_SIZE)
+                    .is_empty()
+            );
+            assert!(
+                contract
+                    .get_contributed_campaign_ids(Address::from([0x03; 20]), 0, MAX_PAGE_SIZE)
+                    .is_empty()
+            );
+        }
+
+        #[ink::test]
+        fn campaign_edits_work() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            assert!(contract.extend_deadline(campaign_id, 1000000100).is_ok());
+            assert_eq!(
+                contract.extend_deadline(campaign_id, 1000000100),
+                Err(Error::InvalidParameters)
+            );
+            assert!(
+                contract
+                    .update_campaign_metadata(
+                        campaign_id,
+                        String::from("New Title"),
+                        String::from("New Description"),
+                    )
+                    .is_ok()
+            );
+
+            let campaign = contract.get_campaign(campaign_id).unwrap();
+            assert_eq!(campaign.deadline, 1000000100);
+            assert_eq!(campaign.title, "New Title");
+            assert_eq!(campaign.description, "New Description");
+
+            // Only the owner may edit
+            set_caller(Address::from([0x02; 20]));
+            assert_eq!(
+                contract.extend_deadline(campaign_id, 1000000200),
+                Err(Error::OnlyOwner)
+            );
+        }
+
+        #[ink::test]
+        fn lock_on_first_contribution_freezes_campaign() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(
+                token_address,
+                Config {
+                    lock_on_first_contribution: true,
+                    ..Config::default()
+                },
+            );
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            // Edits succeed before any contribution
+            assert!(contract.extend_deadline(campaign_id, 1000000100).is_ok());
+
+            let contributor = Address::from([0x02; 20]);
+            let campaign = contract.get_campaign(campaign_id).unwrap();
+            contract
+                .record_contribution(campaign, contributor, contributor, U256::from(1))
+                .unwrap();
+
+            assert_eq!(
+                contract.extend_deadline(campaign_id, 1000000200),
+                Err(Error::CampaignLocked)
+            );
+            assert_eq!(
+                contract.update_campaign_metadata(
+                    campaign_id,
+                    String::from("New Title"),
+                    String::from("New Description"),
+                ),
+                Err(Error::CampaignLocked)
+            );
+            assert_eq!(
+                contract.get_campaign(campaign_id).unwrap().deadline,
+                1000000100
+            );
+        }
+
+        #[ink::test]
+        fn campaign_exists_works() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            assert!(!contract.campaign_exists(0));
+
+            set_block_timestamp(500000000);
+            for _ in 0..2 {
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            }
+
+            assert!(contract.campaign_exists(0));
+            assert!(contract.campaign_exists(1));
+            assert!(!contract.campaign_exists(2));
+            assert!(!contract.campaign_exists(u32::MAX));
+        }
+
+        #[ink::test]
+        fn renounce_ownership_non_admin_fails() {
+            let token_address = Address::from([0x42; 20]);
+            let admin = Address::from([0x01; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+
+            set_caller(Address::from([0x02; 20]));
+            assert_eq!(contract.renounce_ownership(), Err(Error::NotAuthorized));
+            assert_eq!(contract.get_admin(), Some(admin));
+        }
+
+        #[ink::test]
+        fn two_step_ownership_transfer_works() {
+            let token_address = Address::from([0x42; 20]);
+            let admin = Address::from([0x01; 20]);
+            let new_admin = Address::from([0x02; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+
+            assert!(contract.transfer_ownership(new_admin).is_ok());
+            assert_eq!(contract.pending_owner(), Some(new_admin));
+            assert_eq!(contract.get_admin(), Some(admin));
+
+            // A different account cannot accept
+            set_caller(Address::from([0x03; 20]));
+            assert_eq!(contract.accept_ownership(), Err(Error::NotAuthorized));
+
+            set_caller(new_admin);
+            assert!(contract.accept_ownership().is_ok());
+            assert_eq!(contract.get_admin(), Some(new_admin));
+            assert_eq!(contract.pending_owner(), None);
+            assert!(contract.pause().is_ok());
+
+            set_caller(admin);
+            assert_eq!(contract.unpause(), Err(Error::NotAuthorized));
+        }
+
+        #[ink::test]
+        fn cancel_ownership_transfer_works() {
+            let token_address = Address::from([0x42; 20]);
+            let admin = Address::from([0x01; 20]);
+            let new_admin = Address::from([0x02; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+
+            assert!(contract.transfer_ownership(new_admin).is_ok());
+            assert!(contract.cancel_ownership_transfer().is_ok());
+            assert_eq!(contract.pending_owner(), None);
+
+            set_caller(new_admin);
+            assert_eq!(contract.accept_ownership(), Err(Error::NotAuthorized));
+            assert_eq!(
+                contract.cancel_ownership_transfer(),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(contract.get_admin(), Some(admin));
+        }
+
+        #[ink::test]
+        fn create_campaign_validates_soft_cap() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let mut create = |options| {
+                contract.create_campaign(
+                    String::from("Test Campaign"),
+                    String::from("Description"),
+                    U256::from(1000),
+                    1000000000,
+                    options,
+                )
+            };
+
+            // No soft cap requires the full goal
+            let campaign = create(CampaignOptions::default()).unwrap();
+            assert_eq!(campaign.soft_cap, U256::from(1000));
+
+            // Soft cap above the goal
+            let result = create(CampaignOptions {
+                soft_cap: U256::from(1001),
+                ..CampaignOptions::default()
+            });
+            assert_eq!(result, Err(Error::InvalidParameters));
+
+            // Milestones need the full goal
+            let result = create(CampaignOptions {
+                milestones: vec![U256::from(1000)],
+                soft_cap: U256::from(600),
+                ..CampaignOptions::default()
+            });
+            assert_eq!(result, Err(Error::InvalidParameters));
+        }
+
+        #[ink::test]
+        fn goal_currency_round_trips() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let mut create = |goal_currency: &str| {
+                contract.create_campaign(
+                    String::from("Test Campaign"),
+                    String::from("Description"),
+                    U256::from(1000),
+                    1000000000,
+                    CampaignOptions {
+                        goal_currency: String::from(goal_currency),
+                        ..CampaignOptions::default()
+                    },
+                )
+            };
+
+            let campaign_id = create("€").unwrap().id;
+            assert!(create(&"X".repeat(MAX_GOAL_CURRENCY_LEN)).is_ok());
+            assert_eq!(
+                create(&"X".repeat(MAX_GOAL_CURRENCY_LEN + 1)),
+                Err(Error::InvalidParameters)
+            );
+
+            assert_eq!(
+                contract.get_campaign(campaign_id).unwrap().goal_currency,
+                "€"
+            );
+            assert_eq!(
+                contract.get_campaign(1).unwrap().goal_currency,
+                "X".repeat(MAX_GOAL_CURRENCY_LEN)
+            );
+        }
+
+        #[ink::test]
+        fn get_campaigns_reports_per_id() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let first = contract
+                .create_campaign(
+                    String::from("First Campaign"),
+                    String::from("Description"),
+                    U256::from(1000),
+                    1000000000,
+                    CampaignOptions::default(),
+                )
+                .unwrap();
+            let second = contract
+                .create_campaign(
+                    String::from("Second Campaign"),
+                    String::from("Description"),
+                    U256::from(2000),
+                    1000000000,
+                    CampaignOptions::default(),
+                )
+                .unwrap();
+
+            assert_eq!(
+                contract.get_campaigns(vec![1, 5, 0, 1]),
+                Ok(vec![
+                    Ok(second.clone()),
+                    Err(Error::CampaignNotFound),
+                    Ok(first),
+                    Ok(second),
+                ])
+            );
+            assert_eq!(contract.get_campaigns(Vec::new()), Ok(Vec::new()));
+            assert_eq!(
+                contract.get_campaigns(vec![0; MAX_PAGE_SIZE as usize + 1]),
+                Err(Error::InvalidParameters)
+            );
+        }
+
+        #[ink::test]
+        fn can_finalize_works() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(
+                token_address,
+                Config {
+                    grace_period: 100,
+                    ..Config::default()
+                },
+            );
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            // Deadline not reached, then within the grace period
+            assert_eq!(contract.can_finalize(campaign_id, owner), Ok(false));
+            set_block_timestamp(1000000100);
+            assert_eq!(contract.can_finalize(campaign_id, owner), Ok(false));
+
+            // Ready, but only for the owner
+            set_block_timestamp(1000000101);
+            assert_eq!(contract.can_finalize(campaign_id, owner), Ok(true));
+            assert_eq!(
+                contract.can_finalize(campaign_id, Address::from([0x02; 20])),
+                Ok(false)
+            );
+
+            // Already finalized
+            assert_eq!(contract.finalize(campaign_id), Ok(()));
+            assert_eq!(contract.can_finalize(campaign_id, owner), Ok(false));
+
+            assert_eq!(
+                contract.can_finalize(1, owner),
+                Err(Error::CampaignNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn get_all_campaign_summaries_matches_full_reads() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            assert!(contract.get_all_campaign_summaries().is_empty());
+
+            for i in 0..4 {
+                contract
+                    .create_campaign(
+                        format!("Campaign {}", i),
+                        "A very long description. ".repeat(100),
+                        U256::from(1000 + i as u128),
+                        1000000000 + i as u64,
+                        CampaignOptions::default(),
+                    )
+                    .unwrap();
+            }
+            set_raised(&mut contract, 2, U256::from(400));
+
+            let campaigns = contract.get_all_campaigns();
+            let summaries = contract.get_all_campaign_summaries();
+            assert_eq!(
+                summaries,
+                campaigns
+                    .iter()
+                    .map(CampaignSummary::from)
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(summaries[2].raised, U256::from(400));
+
+            // The return value is a fraction of the full read
+            let full = ink::scale::Encode::encoded_size(&campaigns);
+            let compact = ink::scale::Encode::encoded_size(&summaries);
+            assert!(compact < full / 10);
+        }
+
+        #[ink::test]
+        fn get_campaign_deadline_status_works() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            let deadline = 1000000000;
+            let mut create =
+                || create_test_campaign(&mut contract, 1000, deadline, CampaignOptions::default());
+            let failed_id = create();
+            let succeeded_id = create();
+            set_raised(&mut contract, succeeded_id, U256::from(1000));
+
+            assert_eq!(contract.get_campaign_deadline_status(failed_id), Ok(0));
+            set_block_timestamp(deadline + 1);
+            assert_eq!(contract.get_campaign_deadline_status(failed_id), Ok(1));
+
+            assert!(contract.finalize(failed_id).is_ok());
+            assert!(contract.finalize(succeeded_id).is_ok());
+            assert_eq!(contract.get_campaign_deadline_status(succeeded_id), Ok(2));
+            assert_eq!(contract.get_campaign_deadline_status(failed_id), Ok(3));
+
+            assert_eq!(
+                contract.get_campaign_deadline_status(2),
+                Err(Error::CampaignNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn funding_velocity_works() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            set_raised(&mut contract, campaign_id, U256::from(1000));
+
+            // No time elapsed yet
+            assert_eq!(contract.funding_velocity(campaign_id), Ok(U256::zero()));
+
+            set_block_timestamp(500000000 + 250);
+            assert_eq!(contract.funding_velocity(campaign_id), Ok(U256::from(4)));
+
+            // Rounds down
+            set_block_timestamp(500000000 + 300);
+            assert_eq!(contract.funding_velocity(campaign_id), Ok(U256::from(3)));
+
+            assert_eq!(contract.funding_velocity(1), Err(Error::CampaignNotFound));
+        }
+
+        #[ink::test]
+        fn create_campaigns_works() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            contract
+                .create_campaign(
+                    String::from("Existing"),
+                    String::from("Description"),
+                    U256::from(1000),
+                    1000000000,
+                    CampaignOptions::default(),
+                )
+                .unwrap();
+
+            let specs = (1..=3u32)
+                .map(|month| {
+                    (
+                        format!("Drive {}", month),
+                        String::from("Monthly drive"),
+                        U256::from(1000),
+                        1000000000 + u64::from(month),
+                    )
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(contract.create_campaigns(specs), Ok(vec![1, 2, 3]));
+            assert_eq!(contract.get_campaign_count(), 4);
+            let campaign = contract.get_campaign(3).unwrap();
+            assert_eq!(campaign.title, "Drive 3");
+            assert_eq!(campaign.deadline, 1000000003);
+        }
+
+        #[ink::test]
+        fn create_campaigns_is_all_or_none() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let spec = |goal: u128| {
+                (
+                    String::from("Drive"),
+                    String::from("Monthly drive"),
+                    U256::from(goal),
+                    1000000000,
+                )
+            };
+
+            // Zero goal in the last spec
+            assert_eq!(
+                contract.create_campaigns(vec![spec(1000), spec(1000), spec(0)]),
+                Err(Error::InvalidParameters)
+            );
+            assert_eq!(contract.get_campaign_count(), 0);
+
+            assert_eq!(
+                contract.create_campaigns(Vec::new()),
+                Err(Error::InvalidParameters)
+            );
+            assert_eq!(
+                contract.create_campaigns(vec![spec(1000); MAX_CAMPAIGN_BATCH_LEN + 1]),
+                Err(Error::InvalidParameters)
+            );
+            assert_eq!(contract.get_campaign_count(), 0);
+        }
+
+        #[ink::test]
+        fn remove_campaign_clears_empty_campaigns() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            for i in 0..3 {
+                contract
+                    .create_campaign(
+                        format!("Campaign {}", i),
+                        String::from("Description"),
+                        U256::from(1000),
+                        1000000000,
+                        CampaignOptions::default(),
+                    )
+                    .unwrap();
+            }
+
+            assert_eq!(contract.remove_campaign(1), Ok(()));
+            assert_eq!(contract.get_campaign(1), Err(Error::CampaignNotFound));
+            assert!(!contract.campaign_exists(1));
+            assert_eq!(contract.remove_campaign(1), Err(Error::CampaignNotFound));
+
+            // Other campaigns keep their IDs
+            assert_eq!(contract.get_campaign(0).unwrap().title, "Campaign 0");
+            assert_eq!(contract.get_campaign(2).unwrap().title, "Campaign 2");
+            assert_eq!(contract.get_all_campaign_ids(), vec![0, 2]);
+            assert_eq!(contract.get_all_campaigns().len(), 2);
+            assert_eq!(contract.get_campaign_count(), 3);
+            assert_eq!(contract.get_active_campaign_count(), 2);
+
+            // New campaigns don't reuse the removed ID
+            let campaign = contract
+                .create_campaign(
+                    String::from("Campaign 3"),
+                    String::from("Description"),
+                    U256::from(1000),
+                    1000000000,
+                    CampaignOptions::default(),
+                )
+                .unwrap();
+            assert_eq!(campaign.id, 3);
+        }
+
+        #[ink::test]
+        fn on_token_transfer_credits_the_transferrer() {
+            let token_address = Address::from([0x42; 20]);
+            let contributor = Address::from([0x02; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            let data = campaign_id.to_le_bytes();
+
+            // Only the token can report a transfer
+            set_caller(contributor);
+            assert_eq!(
+                contract.credit_token_transfer(contributor, U256::from(300), &data),
+                Err(Error::NotAuthorized)
+            );
+
+            set_caller(token_address);
+            assert_eq!(
+                contract.credit_token_transfer(contributor, U256::from(300), &[0x00]),
+                Err(Error::InvalidParameters)
+            );
+            assert_eq!(
+                contract.credit_token_transfer(contributor, U256::from(300), &7u32.to_le_bytes()),
+                Err(Error::CampaignNotFound)
+            );
+            assert_eq!(
+                contract.credit_token_transfer(contributor, U256::zero(), &data),
+                Err(Error::InvalidParameters)
+            );
+
+            contract.on_token_transfer(contributor, U256::from(300), data.to_vec());
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::from(300)
+            );
+            assert_eq!(
+                contract.get_campaign(campaign_id).unwrap().raised,
+                U256::from(300)
+            );
+            assert_eq!(contract.get_total_value_locked(), U256::from(300));
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "contribution rejected: NotAuthorized")]
+        fn on_token_transfer_from_other_caller_traps() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            set_caller(Address::from([0x02; 20]));
+            contract.on_token_transfer(
+                Address::from([0x02; 20]),
+                U256::from(300),
+                campaign_id.to_le_bytes().to_vec(),
+            );
+        }
+
+        #[ink::test]
+        fn record_contribution_overflow_returns_error() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            let contributor = Address::from([0x02; 20]);
+
+            let campaign = contract.get_campaign(campaign_id).unwrap();
+            contract
+                .record_contribution(campaign, contributor, contributor, U256::from(100))
+                .unwrap();
+            contract.total_raised_all = U256::MAX;
+
+            let campaign = contract.get_campaign(campaign_id).unwrap();
+            assert_eq!(
+                contract.record_contribution(campaign, contributor, contributor, U256::from(1)),
+                Err(Error::Overflow)
+            );
+
+            // Nothing was written
+            assert_eq!(
+                contract.get_campaign(campaign_id).unwrap().raised,
+                U256::from(100)
+            );
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::from(100)
+            );
+            assert_eq!(contract.total_raised_all, U256::MAX);
+        }
+    }
+
+    /// Unit tests exercising the real `TokenRef`, whose calls panic off-chain
+    /// since no token is deployed
+    #[cfg(all(test, not(feature = "mock-token")))]
+    mod cross_contract_tests {
+        use super::*;
+
+        fn set_caller(sender: Address) {
+            ink::env::test::set_caller(sender);
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "failed getting code hash")]
+        fn mint_faucet_cross_contract_call() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+
+            // Set caller
+            let caller = Address::from([0x01; 20]);
+            set_caller(caller);
+
+            // This test verifies that the mint_faucet function attempts a cross-contract call
+            // It will panic because the token address doesn't point to a real contract
+            let amount = U256::from(1000);
+            let _result = contract.mint_faucet(amount);
+
+            // This line should not be reached due to the panic
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "failed getting code hash")]
+        fn contribute_cross_contract_call() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(500000000);
+            let campaign_id = contract
+                .create_campaign(
+                    String::from("Test Campaign"),
+                    String::from("Description"),
+                    U256::from(1000),
+                    1000000000,
+                    CampaignOptions::default(),
+                )
+                .unwrap()
+                .id;
+
+            // The allowance preflight is the first call into the token
+            set_caller(Address::from([0x02; 20]));
+            let _result = contract.contribute(campaign_id, U256::from(100));
+        }
+    }
+
+    /// Unit tests running against the token mock, see `mock_token`
+    #[cfg(all(test, feature = "mock-token"))]
+    mod mock_tests {
+        use super::tests::{create_test_campaign, set_block_timestamp, set_caller, set_raised};
+        use super::*;
+
+        /// Address of the contract under test, which the token mock sees as caller
+        fn contract_address() -> Address {
+            ink::env::address()
+        }
+
+        /// Balance `owner` holds of the (mocked) token at `token_address`
+        fn balance_of(token_address: Address, owner: Address) -> U256 {
+            TokenRef::from_addr(token_address).balance_of(owner)
+        }
+
+        /// Gives `account` `amount` tokens at `token_address` and lets the contract
+        /// spend all of them
+        fn fund(token_address: Address, account: Address, amount: U256) {
+            mock_token::set_balance(token_address, account, amount);
+            mock_token::set_allowance(token_address, account, contract_address(), amount);
+        }
+
+        /// Funds `contributor` with `amount` of the campaign's token and contributes
+        /// it, leaving `contributor` as the caller
+        fn contribute_as(
+            contract: &mut InkFundMe,
+            campaign_id: u32,
+            contributor: Address,
+            amount: u128,
+        ) {
+            let token_address = contract.get_campaign_token(campaign_id).unwrap();
+            fund(token_address, contributor, U256::from(amount));
+            set_caller(contributor);
+            contract
+                .contribute(campaign_id, U256::from(amount))
+                .unwrap();
+        }
+
+        /// Returns the address controlled by the secp256k1 `secret` key
+        fn signer_address(secret: [u8; 32]) -> Address {
+            let secp = secp256k1::Secp256k1::new();
+            let secret_key = secp256k1::SecretKey::from_byte_array(&secret).unwrap();
+            let public_key = secp256k1::PublicKey::from_secret_key(&secp, &secret_key);
+            let mut address = [0u8; 20];
+            ink::env::ecdsa_to_eth_address(&public_key.serialize(), &mut address).unwrap();
+            Address::from(address)
+        }
+
+        /// Signs a permit letting the contract spend `value` of the tokens of the
+        /// `secret` key's address at `token_address`
+        fn sign_permit(
+            token_address: Address,
+            secret: [u8; 32],
+            value: U256,
+            deadline: u64,
+        ) -> [u8; 65] {
+            let token = TokenRef::from_addr(token_address);
+            let owner = signer_address(secret);
+            let message_hash = token.permit_hash(
+                owner,
+                contract_address(),
+                value,
+                token.nonces(owner),
+                deadline,
+            );
+
+            let secp = secp256k1::Secp256k1::new();
+            let secret_key = secp256k1::SecretKey::from_byte_array(&secret).unwrap();
+            let message = secp256k1::Message::from_digest(message_hash);
+            let (recovery_id, compact) = secp
+                .sign_ecdsa_recoverable(&message, &secret_key)
+                .serialize_compact();
+            let mut signature = [0u8; 65];
+            signature[..64].copy_from_slice(&compact);
+            signature[64] = i32::from(recovery_id) as u8;
+            signature
+        }
+
+        /// Creates a campaign with `goal` 1000, has `contributions` made to it and
+        /// finalizes it as failed
+        fn failed_campaign(contract: &mut InkFundMe, contributions: &[(Address, u128)]) -> u32 {
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(contract, 1000, 1000000000, CampaignOptions::default());
+            for &(contributor, amount) in contributions {
+                contribute_as(contract, campaign_id, contributor, amount);
+            }
+
+            set_caller(owner);
+            set_block_timestamp(1000000001);
+            contract.finalize(campaign_id).unwrap();
+            campaign_id
+        }
+
+        /// Creates a campaign and records contributions from two backers exceeding
+        /// its goal of 1000, then finalizes it
+        fn overfunded_campaign(contract: &mut InkFundMe, first: Address, second: Address) -> u32 {
+            set_block_timestamp(500000000);
+            let owner = ink::env::caller();
+            let campaign_id = create_test_campaign(
+                contract,
+                1000,
+                1000000000,
+                CampaignOptions {
+                    milestones: vec![U256::from(1000)],
+                    ..CampaignOptions::default()
+                },
+            );
+
+            contribute_as(contract, campaign_id, first, 300);
+            contribute_as(contract, campaign_id, second, 900);
+
+            set_caller(owner);
+            set_block_timestamp(1000000001);
+            assert!(contract.finalize(campaign_id).is_ok());
+            campaign_id
+        }
+
+        /// Creates a campaign with a goal of 1000 and a soft cap of 600, has
+        /// `contributor` contribute `raised` to it and finalizes it
+        fn soft_cap_campaign(contract: &mut InkFundMe, contributor: Address, raised: u128) -> u32 {
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            set_block_timestamp(500000000);
+            let campaign_id = create_test_campaign(
+                contract,
+                1000,
+                1000000000,
+                CampaignOptions {
+                    soft_cap: U256::from(600),
+                    ..CampaignOptions::default()
+                },
+            );
+            contribute_as(contract, campaign_id, contributor, raised);
+
+            set_caller(owner);
+            set_block_timestamp(1000000001);
+            contract.finalize(campaign_id).unwrap();
+            campaign_id
+        }
+
+        /// Decodes the most recently emitted `CampaignFinalized` event
+        fn last_finalized_event() -> CampaignFinalized {
+            let event = ink::env::test::recorded_events()
+                .into_iter()
+                .last()
+                .unwrap();
+            <CampaignFinalized as ink::scale::Decode>::decode(&mut &event.data[..]).unwrap()
+        }
+
+        /// Config with a faucet cooldown of 1000 and at most 500 tokens per mint
+        fn faucet_config() -> Config {
+            Config {
+                faucet_cooldown: 1000,
+                faucet_max: U256::from(500),
+                ..Config::default()
+            }
+        }
+
+        /// Config with a refund window of 1000
+        fn refund_window_config() -> Config {
+            Config {
+                refund_window: 1000,
+                ..Config::default()
+            }
+        }
+
+        /// Config charging a creation fee of 50, paid to `[0x09; 20]`
+        fn creation_fee_config() -> Config {
+            Config {
+                creation_fee: U256::from(50),
+                fee_recipient: Address::from([0x09; 20]),
+                ..Config::default()
+            }
+        }
+
+        #[ink::test]
+        fn mint_faucet_mints_to_caller() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+
+            // Set caller
+            let caller = Address::from([0x01; 20]);
+            set_caller(caller);
+
+            let amount = U256::from(1000);
+            assert_eq!(contract.mint_faucet(amount), Ok(()));
+            assert_eq!(balance_of(token_address, caller), amount);
+        }
+
+        #[ink::test]
+        fn release_milestone_before_finalize_fails() {
+            let owner = Address::from([0x01; 20]);
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_caller(owner);
+            set_block_timestamp(500000000);
+
+            let campaign_id = create_test_campaign(
+                &mut contract,
+                1000,
+                1000000000,
+                CampaignOptions {
+                    milestones: vec![U256::from(400), U256::from(600)],
+                    ..CampaignOptions::default()
+                },
+            );
+            set_raised(&mut contract, campaign_id, U256::from(1000));
+
+            let result = contract.release_milestone(campaign_id, 0);
+            assert_eq!(result, Err(Error::NotFinalized));
+        }
+
+        #[ink::test]
+        fn release_milestone_checks_caller_and_index() {
+            let owner = Address::from([0x01; 20]);
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_caller(owner);
+            set_block_timestamp(500000000);
+
+            let campaign_id = create_test_campaign(
+                &mut contract,
+                1000,
+                1000000000,
+                CampaignOptions {
+                    milestones: vec![U256::from(400), U256::from(600)],
+                    ..CampaignOptions::default()
+                },
+            );
+            set_raised(&mut contract, campaign_id, U256::from(1000));
+
+            // Raised exactly the goal, so finalizing transfers nothing up front
+            set_block_timestamp(1000000001);
+            assert!(contract.finalize(campaign_id).is_ok());
+
+            assert_eq!(
+                contract.release_milestone(campaign_id, 2),
+                Err(Error::InvalidParameters)
+            );
+
+            set_caller(Address::from([0x02; 20]));
+            assert_eq!(
+                contract.release_milestone(campaign_id, 0),
+                Err(Error::OnlyOwner)
+            );
+        }
+
+        #[ink::test]
+        fn release_milestone_twice_fails() {
+            let owner = Address::from([0x01; 20]);
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_caller(owner);
+            set_block_timestamp(500000000);
+
+            let campaign_id = create_test_campaign(
+                &mut contract,
+                1000,
+                1000000000,
+                CampaignOptions {
+                    milestones: vec![U256::from(400), U256::from(600)],
+                    ..CampaignOptions::default()
+                },
+            );
+            set_raised(&mut contract, campaign_id, U256::from(1000));
+            set_block_timestamp(1000000001);
+            assert!(contract.finalize(campaign_id).is_ok());
+
+            // Simulate an earlier release of the first tranche
+            let mut campaign = contract.get_campaign(campaign_id).unwrap();
+            campaign.milestones[0].1 = true;
+            contract.campaigns.set(campaign_id, &campaign);
+
+            assert_eq!(
+                contract.release_milestone(campaign_id, 0),
+                Err(Error::MilestoneAlreadyReleased)
+            );
+        }
+
+        #[ink::test]
+        fn release_milestone_transfers_tranche() {
+            let owner = Address::from([0x01; 20]);
+            let contributor = Address::from([0x02; 20]);
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_caller(owner);
+            set_block_timestamp(500000000);
+
+            let campaign_id = create_test_campaign(
+                &mut contract,
+                1000,
+                1000000000,
+                CampaignOptions {
+                    milestones: vec![U256::from(400), U256::from(600)],
+                    ..CampaignOptions::default()
+                },
+            );
+            contribute_as(&mut contract, campaign_id, contributor, 1000);
+            set_caller(owner);
+            set_block_timestamp(1000000001);
+            assert!(contract.finalize(campaign_id).is_ok());
+
+            // Only the released tranche leaves the contract
+            assert_eq!(contract.release_milestone(campaign_id, 0), Ok(()));
+            assert_eq!(balance_of(token_address, owner), U256::from(400));
+            assert_eq!(
+                balance_of(token_address, contract_address()),
+                U256::from(600)
+            );
+        }
+
+        #[ink::test]
+        fn grace_period_boundaries() {
+            let owner = Address::from([0x01; 20]);
+            let token_address = Address::from([0x42; 20]);
+            let grace_period = 100;
+            let deadline = 1000000000;
+            let mut contract = InkFundMe::new(
+                token_address,
+                Config {
+                    grace_period,
+                    ..Default::default()
+                },
+            );
+            set_caller(owner);
+            set_block_timestamp(500000000);
+
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, deadline, CampaignOptions::default());
+            let campaign = contract.get_campaign(campaign_id).unwrap();
+
+            // At the deadline: contributions accepted, finalize blocked
+            set_block_timestamp(deadline);
+            assert!(contract.ensure_accepting_contributions(&campaign).is_ok());
+            assert_eq!(
+                contract.finalize(campaign_id),
+                Err(Error::DeadlineNotReached)
+            );
+
+            // At the end of the grace period: contributions accepted, finalize blocked
+            set_block_timestamp(deadline + grace_period);
+            assert!(contract.ensure_accepting_contributions(&campaign).is_ok());
+            assert_eq!(
+                contract.finalize(campaign_id),
+                Err(Error::DeadlineNotReached)
+            );
+
+            // Past the grace period: contributions rejected, finalize allowed
+            set_block_timestamp(deadline + grace_period + 1);
+            assert_eq!(
+                contract.contribute(campaign_id, U256::from(100)),
+                Err(Error::DeadlineReached)
+            );
+            assert!(contract.finalize(campaign_id).is_ok());
+        }
+
+        #[ink::test]
+        fn required_allowance_unknown_campaign_fails() {
+            let token_address = Address::from([0x42; 20]);
+            let contract = InkFundMe::new(token_address, Config::default());
+
+            assert_eq!(
+                contract.required_allowance(0, Address::from([0x02; 20]), U256::from(300)),
+                Err(Error::CampaignNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn contribution_for_beneficiary_is_refundable_by_beneficiary() {
+            let payer = Address::from([0x01; 20]);
+            let beneficiary = Address::from([0x02; 20]);
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            // A pays for B (the token transfer itself is covered by `contribute_for`)
+            let campaign = contract.get_campaign(campaign_id).unwrap();
+            contract
+                .record_contribution(campaign, payer, beneficiary, U256::from(300))
+                .unwrap();
+
+            assert_eq!(
+                contract.get_contribution(campaign_id, beneficiary),
+                U256::from(300)
+            );
+            assert_eq!(contract.get_contribution(campaign_id, payer), U256::zero());
+
+            // The campaign fails
+            set_block_timestamp(1000000001);
+            assert!(contract.finalize(campaign_id).is_ok());
+
+            // The payer has nothing to claim
+            set_caller(payer);
+            assert_eq!(
+                contract.claim_refund(campaign_id),
+                Err(Error::NoContribution)
+            );
+        }
+
+        #[ink::test]
+        fn contribution_for_beneficiary_refund_transfers_to_beneficiary() {
+            let owner = Address::from([0x03; 20]);
+            let payer = Address::from([0x01; 20]);
+            let beneficiary = Address::from([0x02; 20]);
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_caller(owner);
+            set_block_timestamp(500000000);
+
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            fund(token_address, payer, U256::from(300));
+            set_caller(payer);
+            assert_eq!(
+                contract.contribute_for(campaign_id, beneficiary, U256::from(300)),
+                Ok(())
+            );
+
+            set_caller(owner);
+            set_block_timestamp(1000000001);
+            assert!(contract.finalize(campaign_id).is_ok());
+
+            // The refund goes to the beneficiary, not the payer
+            set_caller(beneficiary);
+            assert_eq!(contract.claim_refund(campaign_id), Ok(()));
+            assert_eq!(balance_of(token_address, beneficiary), U256::from(300));
+            assert_eq!(balance_of(token_address, payer), U256::zero());
+        }
+
+        #[ink::test]
+        fn claim_refund_to_checks_recipient_and_contribution() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let contributor = Address::from([0x02; 20]);
+            let cold_wallet = Address::from([0x03; 20]);
+            let campaign_id = failed_campaign(&mut contract, &[(contributor, 300)]);
+
+            set_caller(contributor);
+            assert_eq!(
+                contract.claim_refund_to(campaign_id, Address::zero()),
+                Err(Error::InvalidParameters)
+            );
+
+            // The refund is keyed off the caller, not the recipient
+            set_caller(cold_wallet);
+            assert_eq!(
+                contract.claim_refund_to(campaign_id, cold_wallet),
+                Err(Error::NoContribution)
+            );
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::from(300)
+            );
+        }
+
+        #[ink::test]
+        fn claim_refund_to_transfers_to_recipient() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let contributor = Address::from([0x02; 20]);
+            let recipient = Address::from([0x03; 20]);
+            let campaign_id = failed_campaign(&mut contract, &[(contributor, 300)]);
+
+            // The 300 go to the cold wallet and the caller's contribution is cleared
+            set_caller(contributor);
+            assert_eq!(contract.claim_refund_to(campaign_id, recipient), Ok(()));
+            assert_eq!(balance_of(token_address, recipient), U256::from(300));
+            assert_eq!(balance_of(token_address, contributor), U256::zero());
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::zero()
+            );
+        }
+
+        #[ink::test]
+        fn contribute_with_permit_closed_campaign_fails() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            // Rejected before the permit is submitted to the token
+            set_block_timestamp(1000000001);
+            let result =
+                contract.contribute_with_permit(campaign_id, U256::from(100), 1000000001, [0; 65]);
+            assert_eq!(result, Err(Error::DeadlineReached));
+
+            let result = contract.contribute_with_permit(1, U256::from(100), 1000000001, [0; 65]);
+            assert_eq!(result, Err(Error::CampaignNotFound));
+        }
+
+        #[ink::test]
+        fn contribute_with_permit_submits_permit() {
+            let token_address = Address::from([0x42; 20]);
+            let secret = [0x11; 32];
+            let contributor = signer_address(secret);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            // No prior approval, the permit grants the allowance spent right away
+            mock_token::set_balance(token_address, contributor, U256::from(100));
+            let signature = sign_permit(token_address, secret, U256::from(100), 1000000000);
+            set_caller(contributor);
+            assert_eq!(
+                contract.contribute_with_permit(
+                    campaign_id,
+                    U256::from(100),
+                    1000000000,
+                    signature
+                ),
+                Ok(())
+            );
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::from(100)
+            );
+            assert_eq!(
+                balance_of(token_address, contract_address()),
+                U256::from(100)
+            );
+
+            // The signature was consumed with its nonce
+            mock_token::set_balance(token_address, contributor, U256::from(100));
+            assert_eq!(
+                contract.contribute_with_permit(
+                    campaign_id,
+                    U256::from(100),
+                    1000000000,
+                    signature
+                ),
+                Err(Error::TokenError(TokenError::InvalidSignature))
+            );
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::from(100)
+            );
+        }
+
+        #[ink::test]
+        fn contribute_with_permit_rejects_foreign_signature() {
+            let token_address = Address::from([0x42; 20]);
+            let contributor = signer_address([0x11; 32]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            mock_token::set_balance(token_address, contributor, U256::from(100));
+            set_caller(contributor);
+
+            // Signed by another key
+            let signature = sign_permit(token_address, [0x22; 32], U256::from(100), 1000000000);
+            assert_eq!(
+                contract.contribute_with_permit(
+                    campaign_id,
+                    U256::from(100),
+                    1000000000,
+                    signature
+                ),
+                Err(Error::TokenError(TokenError::InvalidSignature))
+            );
+            assert_eq!(
+                contract.contribute_with_permit(campaign_id, U256::from(100), 1000000000, [0; 65]),
+                Err(Error::TokenError(TokenError::InvalidSignature))
+            );
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::zero()
+            );
+            assert_eq!(
+                TokenRef::from_addr(token_address).allowance(contributor, contract_address()),
+                U256::zero()
+            );
+        }
+
+        #[ink::test]
+        fn get_overfunding_refund_is_pro_rata() {
+            let owner = Address::from([0x01; 20]);
+            let first = Address::from([0x02; 20]);
+            let second = Address::from([0x03; 20]);
+            let token_address = Address::from([0x42; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let campaign_id = overfunded_campaign(&mut contract, first, second);
+
+            // 200 overfunded out of 1200 raised
+            assert_eq!(
+                contract.get_overfunding_refund(campaign_id, first),
+                Ok(U256::from(50))
+            );
+            assert_eq!(
+                contract.get_overfunding_refund(campaign_id, second),
+                Ok(U256::from(150))
+            );
+            assert_eq!(
+                contract.get_overfunding_refund(campaign_id, owner),
+                Ok(U256::zero())
+            );
+        }
+
+        #[ink::test]
+        fn claim_overfunding_refund_twice_fails() {
+            let owner = Address::from([0x01; 20]);
+            let first = Address::from([0x02; 20]);
+            let second = Address::from([0x03; 20]);
+            let token_address = Address::from([0x42; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let campaign_id = overfunded_campaign(&mut contract, first, second);
+
+            // Simulate an earlier claim
+            contract
+                .overfunding_claimed
+                .insert((campaign_id, first), &true);
+
+            set_caller(first);
+            assert_eq!(
+                contract.claim_overfunding_refund(campaign_id),
+                Err(Error::AlreadyClaimed)
+            );
+
+            // Nothing to claim without a contribution
+            set_caller(owner);
+            assert_eq!(
+                contract.claim_overfunding_refund(campaign_id),
+                Err(Error::NoContribution)
+            );
+        }
+
+        #[ink::test]
+        fn claim_overfunding_refund_transfers_share() {
+            let owner = Address::from([0x01; 20]);
+            let first = Address::from([0x02; 20]);
+            let second = Address::from([0x03; 20]);
+            let token_address = Address::from([0x42; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let campaign_id = overfunded_campaign(&mut contract, first, second);
+
+            // 300 * 200 / 1200 is sent back and deducted from the contribution
+            set_caller(first);
+            assert_eq!(contract.claim_overfunding_refund(campaign_id), Ok(()));
+            assert_eq!(balance_of(token_address, first), U256::from(50));
+            assert_eq!(
+                contract.get_contribution(campaign_id, first),
+                U256::from(250)
+            );
+        }
+
+        #[ink::test]
+        fn get_refundable_campaigns_lists_failed_campaigns() {
+            let owner = Address::from([0x01; 20]);
+            let contributor = Address::from([0x02; 20]);
+            let token_address = Address::from([0x42; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            // Two campaigns that will fail and one that will succeed
+            for goal in [1000, 2000, 100] {
+                let campaign_id = create_test_campaign(
+                    &mut contract,
+                    goal,
+                    1000000000,
+                    CampaignOptions {
+                        milestones: vec![U256::from(goal)],
+                        ..CampaignOptions::default()
+                    },
+                );
+                let campaign = contract.get_campaign(campaign_id).unwrap();
+                contract
+                    .record_contribution(campaign, contributor, contributor, U256::from(100))
+                    .unwrap();
+            }
+
+            // Nothing is refundable before finalization
+            assert!(
+                contract
+                    .get_refundable_campaigns(contributor, None, None)
+                    .is_empty()
+            );
+
+            set_block_timestamp(1000000001);
+            for campaign_id in 0..3 {
+                assert!(contract.finalize(campaign_id).is_ok());
+            }
+
+            assert_eq!(
+                contract.get_refundable_campaigns(contributor, None, None),
+                vec![(0, U256::from(100)), (1, U256::from(100))]
+            );
+            assert_eq!(
+                contract.get_refundable_campaigns(contributor, Some(1), Some(1)),
+                vec![(1, U256::from(100))]
+            );
+            assert!(
+                contract
+                    .get_refundable_campaigns(owner, None, None)
+                    .is_empty()
+            );
+        }
+        #[ink::test]
+        fn mint_faucet_cooldown_rejects_early_mint() {
+            let token_address = Address::from([0x42; 20]);
+            let caller = Address::from([0x01; 20]);
+            set_caller(caller);
+            let mut contract = InkFundMe::new(token_address, faucet_config());
+            set_block_timestamp(5000);
+
+            // Record a mint as if `mint_faucet` had just succeeded
+            contract.last_faucet.insert(caller, &5000);
+
+            assert_eq!(
+                contract.mint_faucet(U256::from(100)),
+                Err(Error::FaucetCooldown)
+            );
+            set_block_timestamp(5999);
+            assert_eq!(
+                contract.mint_faucet(U256::from(100)),
+                Err(Error::FaucetCooldown)
+            );
+        }
+
+        #[ink::test]
+        fn mint_faucet_rejects_amount_above_max() {
+            let token_address = Address::from([0x42; 20]);
+            set_caller(Address::from([0x01; 20]));
+            let mut contract = InkFundMe::new(token_address, faucet_config());
+
+            assert_eq!(
+                contract.mint_faucet(U256::from(501)),
+                Err(Error::InvalidParameters)
+            );
+        }
+
+        #[ink::test]
+        fn mint_faucet_allowed_after_cooldown() {
+            let token_address = Address::from([0x42; 20]);
+            let caller = Address::from([0x01; 20]);
+            set_caller(caller);
+            let mut contract = InkFundMe::new(token_address, faucet_config());
+            contract.last_faucet.insert(caller, &5000);
+
+            // Cooldown elapsed, so the tokens are minted
+            set_block_timestamp(6000);
+            assert_eq!(contract.mint_faucet(U256::from(500)), Ok(()));
+            assert_eq!(balance_of(token_address, caller), U256::from(500));
+        }
+
+        #[ink::test]
+        fn contribute_max_unknown_campaign_fails() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+
+            assert_eq!(contract.contribute_max(0), Err(Error::CampaignNotFound));
+        }
+
+        #[ink::test]
+        fn contribute_max_after_deadline_fails() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            set_block_timestamp(1000000001);
+            assert_eq!(
+                contract.contribute_max(campaign_id),
+                Err(Error::DeadlineReached)
+            );
+        }
+
+        #[ink::test]
+        fn contribute_max_contributes_lesser_of_balance_and_allowance() {
+            let token_address = Address::from([0x42; 20]);
+            let contributor = Address::from([0x02; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            mock_token::set_balance(token_address, contributor, U256::from(500));
+            mock_token::set_allowance(
+                token_address,
+                contributor,
+                contract_address(),
+                U256::from(300),
+            );
+            set_caller(contributor);
+            assert_eq!(contract.contribute_max(campaign_id), Ok(U256::from(300)));
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::from(300)
+            );
+            assert_eq!(balance_of(token_address, contributor), U256::from(200));
+        }
+
+        #[ink::test]
+        fn contribute_checked_rejects_slippage() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            set_raised(&mut contract, campaign_id, U256::from(900));
+
+            // Only 100 of the 300 would count towards the goal
+            assert_eq!(
+                contract.contribute_checked(campaign_id, U256::from(300), U256::from(200)),
+                Err(Error::SlippageExceeded)
+            );
+
+            // Nothing left to fill
+            set_raised(&mut contract, campaign_id, U256::from(1000));
+            assert_eq!(
+                contract.contribute_checked(campaign_id, U256::from(300), U256::zero()),
+                Err(Error::NothingToContribute)
+            );
+            assert_eq!(
+                contract.contribute_checked(1, U256::from(300), U256::zero()),
+                Err(Error::CampaignNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn contribute_checked_accepts_full_amount() {
+            let token_address = Address::from([0x42; 20]);
+            let contributor = Address::from([0x02; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            // All 300 fit under the goal
+            fund(token_address, contributor, U256::from(300));
+            set_caller(contributor);
+            assert_eq!(
+                contract.contribute_checked(campaign_id, U256::from(300), U256::from(300)),
+                Ok(U256::from(300))
+            );
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::from(300)
+            );
+        }
+
+        #[ink::test]
+        fn contribute_checked_clamps_to_missing_amount() {
+            let token_address = Address::from([0x42; 20]);
+            let contributor = Address::from([0x02; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            contribute_as(&mut contract, campaign_id, Address::from([0x03; 20]), 900);
+
+            // Only the missing 100 are pulled from the contributor
+            fund(token_address, contributor, U256::from(300));
+            set_caller(contributor);
+            assert_eq!(
+                contract.contribute_checked(campaign_id, U256::from(300), U256::from(100)),
+                Ok(U256::from(100))
+            );
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::from(100)
+            );
+            assert_eq!(balance_of(token_address, contributor), U256::from(200));
+            assert_eq!(
+                contract.get_campaign_raised(campaign_id),
+                Ok(U256::from(1000))
+            );
+        }
+
+        #[ink::test]
+        fn refund_all_active_campaign_fails() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            assert_eq!(
+                contract.refund_all(campaign_id, vec![Address::from([0x02; 20])]),
+                Err(Error::CampaignCompleted)
+            );
+        }
+
+        #[ink::test]
+        fn refund_all_skips_addresses_without_contribution() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let campaign_id = failed_campaign(&mut contract, &[]);
+            let stranger = Address::from([0x03; 20]);
+
+            // Anyone may call it, and duplicates without a contribution are no-ops
+            set_caller(stranger);
+            assert!(
+                contract
+                    .refund_all(campaign_id, vec![stranger, stranger])
+                    .is_ok()
+            );
+            assert_eq!(ink::env::test::recorded_events().into_iter().count(), 2); // CampaignCreated + CampaignFinalized
+        }
+
+        #[ink::test]
+        fn refund_all_rejects_oversized_batches() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let campaign_id = failed_campaign(&mut contract, &[]);
+
+            let contributors = vec![Address::from([0x02; 20]); MAX_PAGE_SIZE as usize + 1];
+            assert_eq!(
+                contract.refund_all(campaign_id, contributors),
+                Err(Error::InvalidParameters)
+            );
+        }
+
+        #[ink::test]
+        fn refund_all_transfers_refunds() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let first = Address::from([0x02; 20]);
+            let second = Address::from([0x03; 20]);
+            let campaign_id = failed_campaign(&mut contract, &[(first, 300), (second, 200)]);
+            assert_eq!(contract.get_total_refunded(campaign_id), U256::zero());
+
+            // Listing the first contributor twice doesn't refund them twice
+            assert_eq!(
+                contract.refund_all(campaign_id, vec![first, second, first]),
+                Ok(())
+            );
+            assert_eq!(balance_of(token_address, first), U256::from(300));
+            assert_eq!(balance_of(token_address, second), U256::from(200));
+            assert_eq!(balance_of(token_address, contract_address()), U256::zero());
+            assert_eq!(contract.get_contribution(campaign_id, first), U256::zero());
+            assert_eq!(contract.get_contribution(campaign_id, second), U256::zero());
+            assert_eq!(contract.get_total_refunded(campaign_id), U256::from(500));
+        }
+
+        #[ink::test]
+        fn failed_contribution_changes_nothing() {
+            let token_address = Address::from([0x42; 20]);
+            let contributor = Address::from([0x02; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            let campaign = contract.get_campaign(campaign_id).unwrap();
+
+            // The token rejects the transfer, which aborts the contribution
+            fund(token_address, contributor, U256::from(300));
+            mock_token::set_failing(token_address, true);
+            set_caller(contributor);
+            assert_eq!(
+                contract.contribute(campaign_id, U256::from(300)),
+                Err(Error::TokenError(TokenError::NotAuthorized))
+            );
+
+            assert_eq!(contract.get_campaign(campaign_id), Ok(campaign));
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::zero()
+            );
+            assert!(
+                contract
+                    .get_contributed_campaign_ids(contributor, 0, MAX_PAGE_SIZE)
+                    .is_empty()
+            );
+            assert_eq!(contract.get_total_value_locked(), U256::zero());
+            assert_eq!(balance_of(token_address, contributor), U256::from(300));
+        }
+
+        #[ink::test]
+        fn self_contribution_can_be_forbidden() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            let other = Address::from([0x02; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id = create_test_campaign(
+                &mut contract,
+                1000,
+                1000000000,
+                CampaignOptions {
+                    allow_self_contribution: false,
+                    ..CampaignOptions::default()
+                },
+            );
+
+            // Neither paying nor being credited as the owner works
+            assert_eq!(
+                contract.contribute(campaign_id, U256::from(300)),
+                Err(Error::SelfContribution)
+            );
+            set_caller(other);
+            assert_eq!(
+                contract.contribute_for(campaign_id, owner, U256::from(300)),
+                Err(Error::SelfContribution)
+            );
+            set_caller(owner);
+            ink::env::test::set_value_transferred(U256::from(300));
+            assert_eq!(
+                contract.contribute_native(campaign_id),
+                Err(Error::SelfContribution)
+            );
+        }
+
+        #[ink::test]
+        fn self_contribution_allowed_by_default() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign = contract
+                .create_campaign(
+                    String::from("Test Campaign"),
+                    String::from("Description"),
+                    U256::from(1000),
+                    1000000000,
+                    CampaignOptions::default(),
+                )
+                .unwrap();
+            assert!(campaign.allow_self_contribution);
+
+            // The owner's contribution goes through
+            fund(token_address, owner, U256::from(300));
+            assert_eq!(contract.contribute(campaign.id, U256::from(300)), Ok(()));
+            assert_eq!(
+                contract.get_contribution(campaign.id, owner),
+                U256::from(300)
+            );
+        }
+
+        #[ink::test]
+        fn contribute_zero_amount_fails() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            assert_eq!(
+                contract.contribute(campaign_id, U256::zero()),
+                Err(Error::InvalidParameters)
+            );
+            assert_eq!(
+                contract.contribute_for(campaign_id, Address::from([0x02; 20]), U256::zero()),
+                Err(Error::InvalidParameters)
+            );
+            assert_eq!(
+                contract.contribute_with_permit(campaign_id, U256::zero(), 0, [0; 65]),
+                Err(Error::InvalidParameters)
+            );
+
+            // Only the CampaignCreated event was emitted
+            assert_eq!(ink::env::test::recorded_events().into_iter().count(), 1);
+            assert_eq!(
+                contract.get_campaign(campaign_id).unwrap().raised,
+                U256::zero()
+            );
+        }
+
+        #[ink::test]
+        fn withdraw_stuck_tokens_non_admin_fails() {
+            let token_address = Address::from([0x42; 20]);
+            set_caller(Address::from([0x01; 20]));
+            let mut contract = InkFundMe::new(token_address, Config::default());
+
+            set_caller(Address::from([0x02; 20]));
+            assert_eq!(
+                contract.withdraw_stuck_tokens(U256::from(1)),
+                Err(Error::NotAuthorized)
+            );
+        }
+
+        #[ink::test]
+        fn withdraw_stuck_tokens_withdraws_only_excess() {
+            let token_address = Address::from([0x42; 20]);
+            let admin = Address::from([0x01; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            contribute_as(&mut contract, campaign_id, Address::from([0x02; 20]), 300);
+
+            // 50 tokens sent to the contract directly on top of the 300 locked
+            mock_token::set_balance(token_address, contract_address(), U256::from(350));
+
+            set_caller(admin);
+            assert_eq!(
+                contract.withdraw_stuck_tokens(U256::from(51)),
+                Err(Error::InsufficientExcess)
+            );
+            assert_eq!(contract.withdraw_stuck_tokens(U256::from(50)), Ok(()));
+            assert_eq!(balance_of(token_address, admin), U256::from(50));
+            assert_eq!(
+                balance_of(token_address, contract_address()),
+                U256::from(300)
+            );
+        }
+
+        #[ink::test]
+        fn finalize_event_reports_release() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+
+            // Failed campaign releases nothing
+            let failed_id = failed_campaign(&mut contract, &[(Address::from([0x02; 20]), 300)]);
+            let event = last_finalized_event();
+            assert_eq!(event.campaign_id, failed_id);
+            assert!(!event.success);
+            assert_eq!(event.amount_released, U256::zero());
+            assert_eq!(event.recipient, Address::zero());
+
+            // Successful milestone campaign pays out through `release_milestone`
+            set_block_timestamp(500000000);
+            let campaign_id = create_test_campaign(
+                &mut contract,
+                1000,
+                1000000000,
+                CampaignOptions {
+                    milestones: vec![U256::from(1000)],
+                    ..CampaignOptions::default()
+                },
+            );
+            set_raised(&mut contract, campaign_id, U256::from(1000));
+            set_block_timestamp(1000000001);
+            assert!(contract.finalize(campaign_id).is_ok());
+
+            let event = last_finalized_event();
+            assert_eq!(event.campaign_id, campaign_id);
+            assert!(event.success);
+            assert_eq!(event.amount_released, U256::zero());
+            assert_eq!(event.recipient, owner);
+
+            // Other successful campaigns release the goal to the owner
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            contribute_as(&mut contract, campaign_id, Address::from([0x02; 20]), 1200);
+            set_caller(owner);
+            set_block_timestamp(1000000001);
+            assert!(contract.finalize(campaign_id).is_ok());
+
+            let event = last_finalized_event();
+            assert_eq!(event.campaign_id, campaign_id);
+            assert!(event.success);
+            assert_eq!(event.amount_released, U256::from(1000));
+            assert_eq!(event.recipient, owner);
+        }
+        #[ink::test]
+        fn sweep_unclaimed_before_refund_window_fails() {
+            let token_address = Address::from([0x42; 20]);
+            let admin = Address::from([0x01; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, refund_window_config());
+            let campaign_id = failed_campaign(&mut contract, &[(Address::from([0x02; 20]), 300)]);
+
+            // Deadline is 1000000000, refunds are reserved until 1000001000
+            set_block_timestamp(1000001000);
+            assert_eq!(
+                contract.sweep_unclaimed(campaign_id),
+                Err(Error::DeadlineNotReached)
+            );
+
+            set_caller(Address::from([0x03; 20]));
+            set_block_timestamp(1000001001);
+            assert_eq!(
+                contract.sweep_unclaimed(campaign_id),
+                Err(Error::NotAuthorized)
+            );
+        }
+
+        #[ink::test]
+        fn sweep_unclaimed_disabled_without_refund_window() {
+            let token_address = Address::from([0x42; 20]);
+            set_caller(Address::from([0x01; 20]));
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let campaign_id = failed_campaign(&mut contract, &[]);
+
+            set_block_timestamp(u64::MAX);
+            assert_eq!(
+                contract.sweep_unclaimed(campaign_id),
+                Err(Error::InvalidParameters)
+            );
+        }
+
+        #[ink::test]
+        fn sweep_unclaimed_after_refund_window_closes_refunds() {
+            let token_address = Address::from([0x42; 20]);
+            let admin = Address::from([0x01; 20]);
+            let contributor = Address::from([0x02; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, refund_window_config());
+            let campaign_id = failed_campaign(&mut contract, &[]);
+
+            set_block_timestamp(1000001001);
+            assert!(contract.sweep_unclaimed(campaign_id).is_ok());
+            assert_eq!(
+                contract.sweep_unclaimed(campaign_id),
+                Err(Error::AlreadyClaimed)
+            );
+            // Sweeping doesn't count as a refund
+            assert_eq!(contract.get_total_refunded(campaign_id), U256::zero());
+
+            set_caller(contributor);
+            assert_eq!(
+                contract.claim_refund(campaign_id),
+                Err(Error::AlreadyClaimed)
+            );
+            assert!(
+                contract
+                    .get_refundable_campaigns(contributor, None, None)
+                    .is_empty()
+            );
+        }
+
+        #[ink::test]
+        fn sweep_unclaimed_transfers_unclaimed_refunds() {
+            let token_address = Address::from([0x42; 20]);
+            let admin = Address::from([0x01; 20]);
+            let claimer = Address::from([0x03; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, refund_window_config());
+            let campaign_id = failed_campaign(
+                &mut contract,
+                &[(Address::from([0x02; 20]), 300), (claimer, 200)],
+            );
+            set_caller(claimer);
+            assert_eq!(contract.claim_refund(campaign_id), Ok(()));
+
+            // Only the 300 nobody claimed are left for the admin
+            set_caller(admin);
+            set_block_timestamp(1000001001);
+            assert_eq!(contract.sweep_unclaimed(campaign_id), Ok(()));
+            assert_eq!(balance_of(token_address, admin), U256::from(300));
+            assert_eq!(balance_of(token_address, contract_address()), U256::zero());
+        }
+
+        #[ink::test]
+        fn create_campaign_whole_tokens_scales_goal() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            // Pretend the metadata was already fetched from the token
+            contract.token_metadata = Some((String::from("Test Token"), String::from("TEST"), 18));
+            set_block_timestamp(500000000);
+
+            let campaign = contract
+                .create_campaign_whole_tokens(
+                    String::from("Test Campaign"),
+                    String::from("Description"),
+                    U256::from(5),
+                    1000000000,
+                    vec![U256::from(2), U256::from(3)],
+                )
+                .unwrap();
+
+            let unit = U256::from(10).pow(U256::from(18));
+            assert_eq!(campaign.goal, U256::from(5) * unit);
+            assert_eq!(
+                campaign.milestones,
+                vec![(U256::from(2) * unit, false), (U256::from(3) * unit, false)]
+            );
+        }
+
+        #[ink::test]
+        fn create_campaign_whole_tokens_overflow_fails() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            contract.token_metadata = Some((String::from("Test Token"), String::from("TEST"), 18));
+            set_block_timestamp(500000000);
+
+            let result = contract.create_campaign_whole_tokens(
+                String::from("Test Campaign"),
+                String::from("Description"),
+                U256::MAX,
+                1000000000,
+                Vec::new(),
+            );
+            assert_eq!(result, Err(Error::Overflow));
+            assert_eq!(contract.get_campaign_count(), 0);
+        }
+
+        #[ink::test]
+        fn create_campaign_whole_tokens_fetches_decimals() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            mock_token::set_metadata(token_address, "Test Token", "TEST", 2);
+            set_block_timestamp(500000000);
+
+            // Nothing cached yet, so the token metadata is queried
+            let campaign = contract
+                .create_campaign_whole_tokens(
+                    String::from("Test Campaign"),
+                    String::from("Description"),
+                    U256::from(5),
+                    1000000000,
+                    Vec::new(),
+                )
+                .unwrap();
+            assert_eq!(campaign.goal, U256::from(500));
+            assert_eq!(
+                contract.get_token_metadata(),
+                (String::from("Test Token"), String::from("TEST"), 2)
+            );
+        }
+
+        #[ink::test]
+        fn try_contribute_code_reports_errors() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+
+            assert_eq!(
+                contract.try_contribute_code(0, U256::from(100)),
+                Error::CampaignNotFound.code()
+            );
+
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            assert_eq!(
+                contract.try_contribute_code(campaign_id, U256::zero()),
+                Error::InvalidParameters.code()
+            );
+
+            set_block_timestamp(1000000001);
+            assert_eq!(
+                contract.try_contribute_code(campaign_id, U256::from(100)),
+                Error::DeadlineReached.code()
+            );
+        }
+
+        #[ink::test]
+        fn pause_blocks_creation_and_contributions() {
+            let token_address = Address::from([0x42; 20]);
+            let admin = Address::from([0x01; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            assert!(contract.pause().is_ok());
+            assert!(contract.is_paused());
+
+            assert_eq!(
+                contract.contribute(campaign_id, U256::from(100)),
+                Err(Error::Paused)
+            );
+            assert_eq!(contract.contribute_max(campaign_id), Err(Error::Paused));
+            assert_eq!(
+                contract.create_campaign(
+                    String::from("Test Campaign"),
+                    String::from("Description"),
+                    U256::from(1000),
+                    1000000000,
+                    CampaignOptions::default(),
+                ),
+                Err(Error::Paused)
+            );
+            // Readers stay available
+            assert_eq!(contract.get_campaign_count(), 1);
+
+            assert!(contract.unpause().is_ok());
+            assert!(!contract.is_paused());
+            assert_eq!(
+                contract.contribute(campaign_id, U256::zero()),
+                Err(Error::InvalidParameters)
+            );
+        }
+
+        #[ink::test]
+        fn pause_keeps_refunds_available() {
+            let token_address = Address::from([0x42; 20]);
+            let admin = Address::from([0x01; 20]);
+            let contributor = Address::from([0x02; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let campaign_id = failed_campaign(&mut contract, &[]);
+
+            set_caller(admin);
+            assert!(contract.pause().is_ok());
+
+            // Refund checks run as usual instead of failing with `Paused`
+            set_caller(contributor);
+            assert_eq!(
+                contract.claim_refund(campaign_id),
+                Err(Error::NoContribution)
+            );
+            assert!(contract.refund_all(campaign_id, vec![contributor]).is_ok());
+        }
+
+        #[ink::test]
+        fn renounce_ownership_disables_admin_operations() {
+            let token_address = Address::from([0x42; 20]);
+            let admin = Address::from([0x01; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+
+            assert!(contract.renounce_ownership().is_ok());
+            assert_eq!(contract.get_admin(), None);
+
+            assert_eq!(contract.pause(), Err(Error::NotAuthorized));
+            assert_eq!(
+                contract.set_token_contract(Address::from([0x43; 20])),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(
+                contract.withdraw_stuck_tokens(U256::from(1)),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(contract.renounce_ownership(), Err(Error::NotAuthorized));
+        }
+
+        #[ink::test]
+        fn contribute_native_without_value_fails() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            assert_eq!(
+                contract.contribute_native(campaign_id),
+                Err(Error::InvalidParameters)
+            );
+
+            ink::env::test::set_value_transferred(U256::from(100));
+            assert_eq!(contract.contribute_native(1), Err(Error::CampaignNotFound));
+            assert_eq!(
+                contract.get_campaign(campaign_id).unwrap().raised,
+                U256::zero()
+            );
+        }
+
+        #[ink::test]
+        fn contribute_native_mints_backing_tokens() {
+            let token_address = Address::from([0x42; 20]);
+            let admin = Address::from([0x01; 20]);
+            let contributor = Address::from([0x02; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            // The contract holds the tokens minted to back the contribution
+            set_caller(contributor);
+            ink::env::test::set_value_transferred(U256::from(100));
+            assert_eq!(contract.contribute_native(campaign_id), Ok(()));
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::from(100)
+            );
+            assert_eq!(
+                balance_of(token_address, contract_address()),
+                U256::from(100)
+            );
+
+            // The native value isn't locked, the admin can take it out
+            ink::env::test::set_value_transferred(U256::zero());
+            ink::env::test::set_account_balance(contract_address(), U256::from(100));
+            set_caller(contributor);
+            assert_eq!(
+                contract.withdraw_native(U256::from(100)),
+                Err(Error::NotAuthorized)
+            );
+            set_caller(admin);
+            assert_eq!(
+                contract.withdraw_native(U256::from(101)),
+                Err(Error::NativeTransferFailed)
+            );
+            assert_eq!(contract.withdraw_native(U256::from(100)), Ok(()));
+            let native_balance =
+                ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(
+                    contract_address(),
+                )
+                .unwrap();
+            assert_eq!(native_balance, U256::zero());
+            // The contributions stay backed by tokens
+            assert_eq!(contract.get_total_value_locked(), U256::from(100));
+        }
+
+        #[ink::test]
+        fn contribute_native_fails_after_token_renounce() {
+            let token_address = Address::from([0x42; 20]);
+            let contributor = Address::from([0x02; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            // Minting is frozen once the token owner renounced
+            mock_token::renounce_ownership(token_address);
+            set_caller(contributor);
+            ink::env::test::set_value_transferred(U256::from(100));
+            assert_eq!(
+                contract.contribute_native(campaign_id),
+                Err(Error::TokenError(TokenError::NotAuthorized))
+            );
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::zero()
+            );
+            assert_eq!(contract.get_total_value_locked(), U256::zero());
+        }
+
+        #[ink::test]
+        fn finalize_twice_fails() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let campaign_id = failed_campaign(&mut contract, &[]);
+
+            assert_eq!(contract.finalize(campaign_id), Err(Error::AlreadyFinalized));
+            // Contributing still reports the completed campaign
+            set_block_timestamp(500000000);
+            assert_eq!(
+                contract.contribute(campaign_id, U256::from(100)),
+                Err(Error::CampaignCompleted)
+            );
+        }
+
+        #[ink::test]
+        fn get_contract_token_balance_queries_token() {
+            let token_address = Address::from([0x42; 20]);
+            let contract = InkFundMe::new(token_address, Config::default());
+            mock_token::set_balance(token_address, contract_address(), U256::from(700));
+
+            assert_eq!(contract.get_contract_token_balance(), U256::from(700));
+        }
+
+        #[ink::test]
+        fn finalize_records_claimable_proceeds() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            let contributor = Address::from([0x02; 20]);
+            let campaign = contract.get_campaign(campaign_id).unwrap();
+            contract
+                .record_contribution(campaign, contributor, contributor, U256::from(1200))
+                .unwrap();
+
+            assert_eq!(
+                contract.claim_proceeds(campaign_id),
+                Err(Error::NotFinalized)
+            );
+
+            // Finalizing no longer touches the token
+            set_block_timestamp(1000000001);
+            assert!(contract.finalize(campaign_id).is_ok());
+            assert_eq!(
+                contract.get_claimable_proceeds(campaign_id),
+                U256::from(1000)
+            );
+            // Proceeds stay locked until claimed
+            assert_eq!(contract.get_total_value_locked(), U256::from(1200));
+
+            let event = last_finalized_event();
+            assert!(event.success);
+            assert_eq!(event.amount_released, U256::from(1000));
+            assert_eq!(event.recipient, owner);
+
+            set_caller(contributor);
+            assert_eq!(contract.claim_proceeds(campaign_id), Err(Error::OnlyOwner));
+        }
+
+        #[ink::test]
+        fn claim_proceeds_failed_campaign_fails() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let campaign_id = failed_campaign(&mut contract, &[(Address::from([0x02; 20]), 300)]);
+
+            assert_eq!(contract.claim_proceeds(campaign_id), Err(Error::GoalNotMet));
+            assert_eq!(contract.get_claimable_proceeds(campaign_id), U256::zero());
+        }
+
+        #[ink::test]
+        fn claim_proceeds_transfers_to_owner() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            contribute_as(&mut contract, campaign_id, owner, 1000);
+            set_block_timestamp(1000000001);
+            contract.finalize(campaign_id).unwrap();
+
+            // The proceeds are paid out once
+            assert_eq!(contract.claim_proceeds(campaign_id), Ok(()));
+            assert_eq!(balance_of(token_address, owner), U256::from(1000));
+            assert_eq!(
+                contract.claim_proceeds(campaign_id),
+                Err(Error::AlreadyClaimed)
+            );
+        }
+
+        #[ink::test]
+        fn soft_cap_reached_campaign_succeeds() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let contributor = Address::from([0x02; 20]);
+
+            // Between the soft cap and the goal
+            let campaign_id = soft_cap_campaign(&mut contract, contributor, 800);
+
+            let event = last_finalized_event();
+            assert!(event.success);
+            assert_eq!(event.amount_released, U256::from(800));
+            assert_eq!(
+                contract.get_claimable_proceeds(campaign_id),
+                U256::from(800)
+            );
+
+            set_caller(contributor);
+            assert_eq!(contract.claim_refund(campaign_id), Err(Error::GoalNotMet));
+            assert_eq!(
+                contract.get_overfunding_refund(campaign_id, contributor),
+                Ok(U256::zero())
+            );
+        }
+
+        #[ink::test]
+        fn soft_cap_missed_campaign_is_refundable() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let contributor = Address::from([0x02; 20]);
+
+            // Below the soft cap
+            let campaign_id = soft_cap_campaign(&mut contract, contributor, 500);
+
+            let event = last_finalized_event();
+            assert!(!event.success);
+            assert_eq!(contract.get_claimable_proceeds(campaign_id), U256::zero());
+            assert_eq!(
+                contract.get_refundable_campaigns(contributor, None, None),
+                vec![(campaign_id, U256::from(500))]
+            );
+
+            set_caller(Address::from([0x01; 20]));
+            assert_eq!(contract.claim_proceeds(campaign_id), Err(Error::GoalNotMet));
+        }
+
+        #[ink::test]
+        fn successful_campaign_pays_out_once() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            let first = Address::from([0x02; 20]);
+            let second = Address::from([0x03; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            contribute_as(&mut contract, campaign_id, first, 600);
+            contribute_as(&mut contract, campaign_id, second, 400);
+            assert_eq!(
+                balance_of(token_address, contract_address()),
+                U256::from(1000)
+            );
+
+            set_caller(owner);
+            set_block_timestamp(1000000001);
+            assert_eq!(contract.finalize(campaign_id), Ok(()));
+            assert_eq!(contract.finalize(campaign_id), Err(Error::AlreadyFinalized));
+
+            assert_eq!(contract.claim_proceeds(campaign_id), Ok(()));
+            assert_eq!(
+                contract.claim_proceeds(campaign_id),
+                Err(Error::AlreadyClaimed)
+            );
+            assert_eq!(balance_of(token_address, owner), U256::from(1000));
+            assert_eq!(balance_of(token_address, contract_address()), U256::zero());
+
+            // Contributors of a successful campaign get nothing back
+            set_caller(first);
+            assert_eq!(contract.claim_refund(campaign_id), Err(Error::GoalNotMet));
+            assert_eq!(balance_of(token_address, first), U256::zero());
+        }
+
+        #[ink::test]
+        fn failed_campaign_refunds_once() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            let first = Address::from([0x02; 20]);
+            let second = Address::from([0x03; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let campaign_id = failed_campaign(&mut contract, &[(first, 300), (second, 200)]);
+            assert_eq!(contract.finalize(campaign_id), Err(Error::AlreadyFinalized));
+            assert_eq!(contract.claim_proceeds(campaign_id), Err(Error::GoalNotMet));
+
+            set_caller(first);
+            assert_eq!(contract.claim_refund(campaign_id), Ok(()));
+            assert_eq!(
+                contract.claim_refund(campaign_id),
+                Err(Error::NoContribution)
+            );
+            set_caller(second);
+            assert_eq!(contract.claim_refund(campaign_id), Ok(()));
+
+            assert_eq!(balance_of(token_address, first), U256::from(300));
+            assert_eq!(balance_of(token_address, second), U256::from(200));
+            assert_eq!(balance_of(token_address, owner), U256::zero());
+            assert_eq!(balance_of(token_address, contract_address()), U256::zero());
+            assert_eq!(contract.get_total_refunded(campaign_id), U256::from(500));
+        }
+
+        #[ink::test]
+        fn withdraw_contribution_emits_own_event() {
+            let token_address = Address::from([0x42; 20]);
+            let contributor = Address::from([0x02; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            contribute_as(&mut contract, campaign_id, contributor, 300);
+
+            assert_eq!(
+                contract.withdraw_contribution(campaign_id, U256::from(100)),
+                Ok(())
+            );
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::from(200)
+            );
+            assert_eq!(
+                contract.get_campaign_raised(campaign_id),
+                Ok(U256::from(200))
+            );
+            assert_eq!(contract.get_total_value_locked(), U256::from(200));
+            assert_eq!(balance_of(token_address, contributor), U256::from(100));
+
+            let event = ink::env::test::recorded_events()
+                .into_iter()
+                .last()
+                .unwrap();
+            assert_eq!(
+                &event.topics[0][..],
+                &<ContributionWithdrawn as ink::env::Event>::SIGNATURE_TOPIC.unwrap()[..]
+            );
+            let event = <ContributionWithdrawn as ink::scale::Decode>::decode(&mut &event.data[..])
+                .unwrap();
+            assert_eq!(event.campaign_id, campaign_id);
+            assert_eq!(event.contributor, contributor);
+            assert_eq!(event.amount, U256::from(100));
+            assert_eq!(event.remaining, U256::from(200));
+
+            // Withdrawals count towards the campaign's refunded total
+            assert_eq!(contract.get_total_refunded(campaign_id), U256::from(100));
+            assert_eq!(
+                contract.withdraw_contribution(campaign_id, U256::from(200)),
+                Ok(())
+            );
+            assert_eq!(contract.get_total_refunded(campaign_id), U256::from(300));
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::zero()
+            );
+        }
+
+        #[ink::test]
+        fn withdraw_contribution_validates() {
+            let token_address = Address::from([0x42; 20]);
+            let admin = Address::from([0x01; 20]);
+            let contributor = Address::from([0x02; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            contribute_as(&mut contract, campaign_id, contributor, 300);
+
+            assert_eq!(
+                contract.withdraw_contribution(campaign_id, U256::zero()),
+                Err(Error::InvalidParameters)
+            );
+            assert_eq!(
+                contract.withdraw_contribution(campaign_id, U256::from(301)),
+                Err(Error::InvalidParameters)
+            );
+            set_caller(Address::from([0x03; 20]));
+            assert_eq!(
+                contract.withdraw_contribution(campaign_id, U256::from(1)),
+                Err(Error::NoContribution)
+            );
+
+            // Pausing doesn't lock contributors in
+            set_caller(admin);
+            assert_eq!(contract.pause(), Ok(()));
+            set_caller(contributor);
+            assert_eq!(
+                contract.withdraw_contribution(campaign_id, U256::from(300)),
+                Ok(())
+            );
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::zero()
+            );
+            assert_eq!(contract.get_campaign_raised(campaign_id), Ok(U256::zero()));
+
+            // Nothing can be withdrawn once contributions closed
+            set_caller(admin);
+            assert_eq!(contract.unpause(), Ok(()));
+            contribute_as(&mut contract, campaign_id, contributor, 300);
+            set_block_timestamp(1000000001);
+            assert_eq!(
+                contract.withdraw_contribution(campaign_id, U256::from(100)),
+                Err(Error::DeadlineReached)
+            );
+        }
+
+        #[ink::test]
+        fn get_contributions_works() {
+            let token_address = Address::from([0x42; 20]);
+            let contributor = Address::from([0x02; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            for _ in 0..3 {
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            }
+            contribute_as(&mut contract, 0, contributor, 300);
+            contribute_as(&mut contract, 2, contributor, 500);
+
+            assert_eq!(
+                contract.get_contributions(contributor, vec![2, 1, 0]),
+                Ok(vec![U256::from(500), U256::zero(), U256::from(300)])
+            );
+            // Unknown campaigns read as no contribution
+            assert_eq!(
+                contract.get_contributions(contributor, vec![7]),
+                Ok(vec![U256::zero()])
+            );
+            // Long inputs are rejected rather than truncated
+            assert_eq!(
+                contract
+                    .get_contributions(contributor, vec![0; MAX_PAGE_SIZE as usize])
+                    .map(|contributions| contributions.len()),
+                Ok(MAX_PAGE_SIZE as usize)
+            );
+            assert_eq!(
+                contract.get_contributions(contributor, vec![0; MAX_PAGE_SIZE as usize + 1]),
+                Err(Error::InvalidParameters)
+            );
+        }
+
+        #[ink::test]
+        fn claim_refund_reduces_raised() {
+            let token_address = Address::from([0x42; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let first = Address::from([0x02; 20]);
+            let second = Address::from([0x03; 20]);
+            let campaign_id = failed_campaign(&mut contract, &[(first, 300), (second, 200)]);
+            assert_eq!(
+                contract.get_campaign_raised(campaign_id),
+                Ok(U256::from(500))
+            );
+            assert_eq!(contract.get_campaign_progress(campaign_id), Ok(5000));
+
+            set_caller(first);
+            assert_eq!(contract.claim_refund(campaign_id), Ok(()));
+            assert_eq!(
+                contract.get_campaign_raised(campaign_id),
+                Ok(U256::from(200))
+            );
+            assert_eq!(contract.get_campaign_progress(campaign_id), Ok(2000));
+            assert_eq!(
+                contract.effective_status(campaign_id),
+                Ok(CampaignStatus::Failed)
+            );
+
+            assert_eq!(contract.refund_all(campaign_id, vec![second]), Ok(()));
+            assert_eq!(contract.get_campaign_raised(campaign_id), Ok(U256::zero()));
+            assert_eq!(balance_of(token_address, contract_address()), U256::zero());
+        }
+        #[ink::test]
+        fn create_campaign_collects_creation_fee() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, creation_fee_config());
+            set_block_timestamp(500000000);
+            mock_token::set_balance(token_address, owner, U256::from(80));
+            mock_token::set_allowance(token_address, owner, contract_address(), U256::from(50));
+
+            let result = contract.create_campaign(
+                String::from("Test Campaign"),
+                String::from("Description"),
+                U256::from(1000),
+                1000000000,
+                CampaignOptions::default(),
+            );
+            assert!(result.is_ok());
+            assert_eq!(
+                balance_of(token_address, Address::from([0x09; 20])),
+                U256::from(50)
+            );
+            assert_eq!(balance_of(token_address, owner), U256::from(30));
+            // The fee isn't locked in the contract
+            assert_eq!(contract.get_total_value_locked(), U256::zero());
+        }
+
+        #[ink::test]
+        fn create_campaign_without_fee_approval_fails() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, creation_fee_config());
+            set_block_timestamp(500000000);
+            let mut create = |contract: &mut InkFundMe| {
+                contract.create_campaign(
+                    String::from("Test Campaign"),
+                    String::from("Description"),
+                    U256::from(1000),
+                    1000000000,
+                    CampaignOptions::default(),
+                )
+            };
+
+            // Not approved
+            mock_token::set_balance(token_address, owner, U256::from(50));
+            assert_eq!(create(&mut contract), Err(Error::InsufficientAllowance));
+
+            // Approved but not held
+            mock_token::set_balance(token_address, owner, U256::from(49));
+            mock_token::set_allowance(token_address, owner, contract_address(), U256::from(50));
+            assert_eq!(create(&mut contract), Err(Error::InsufficientTokenBalance));
+
+            assert_eq!(contract.get_campaign_count(), 0);
+            assert_eq!(contract.get_active_campaign_count(), 0);
+            assert_eq!(balance_of(token_address, owner), U256::from(49));
+        }
+
+        #[ink::test]
+        fn enable_refunds_unwinds_unpaid_campaign() {
+            let token_address = Address::from([0x42; 20]);
+            let admin = Address::from([0x01; 20]);
+            let contributor = Address::from([0x02; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            contribute_as(&mut contract, campaign_id, contributor, 1000);
 
-            let result =
-                contract.create_campaign(title.clone(), description.clone(), goal, deadline);
-            assert!(result.is_ok());
+            // Only finalized campaigns, and only by the admin
+            set_caller(admin);
+            assert_eq!(
+                contract.enable_refunds(campaign_id),
+                Err(Error::NotFinalized)
+            );
+            set_block_timestamp(1000000001);
+            assert_eq!(contract.finalize(campaign_id), Ok(()));
+            set_caller(contributor);
+            assert_eq!(
+                contract.enable_refunds(campaign_id),
+                Err(Error::NotAuthorized)
+            );
 
-            let campaign_id = result.unwrap();
-            assert_eq!(campaign_id, 0);
-            assert_eq!(contract.get_campaign_count(), 1);
+            set_caller(admin);
+            assert_eq!(contract.enable_refunds(campaign_id), Ok(()));
+            assert_eq!(
+                contract.effective_status(campaign_id),
+                Ok(CampaignStatus::Failed)
+            );
+            assert_eq!(contract.get_completed_campaign_count(), 1);
+            assert_eq!(contract.get_active_campaign_count(), 0);
+            // Refundable only once
+            assert_eq!(
+                contract.enable_refunds(campaign_id),
+                Err(Error::InvalidParameters)
+            );
 
-            let campaign = contract.get_campaign(campaign_id).unwrap();
-            assert_eq!(campaign.title, title);
-            assert_eq!(campaign.description, description);
-            assert_eq!(campaign.goal, goal);
-            assert_eq!(campaign.deadline, deadline);
-            assert_eq!(campaign.raised, U256::zero());
-            assert!(!campaign.completed);
+            // The owner can no longer claim, contributors get their funds back
+            assert_eq!(contract.claim_proceeds(campaign_id), Err(Error::GoalNotMet));
+            set_caller(contributor);
+            assert_eq!(contract.claim_refund(campaign_id), Ok(()));
+            assert_eq!(balance_of(token_address, contributor), U256::from(1000));
         }
 
         #[ink::test]
-        fn create_campaign_invalid_parameters() {
+        fn enable_refunds_after_payout_fails() {
             let token_address = Address::from([0x42; 20]);
-            let mut contract = InkFundMe::new(token_address);
+            let admin = Address::from([0x01; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, Config::default());
 
-            // Set a current timestamp
+            // Proceeds claimed
             set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            contribute_as(&mut contract, campaign_id, Address::from([0x02; 20]), 1000);
+            set_caller(admin);
+            set_block_timestamp(1000000001);
+            assert_eq!(contract.finalize(campaign_id), Ok(()));
+            assert_eq!(contract.claim_proceeds(campaign_id), Ok(()));
+            assert_eq!(
+                contract.enable_refunds(campaign_id),
+                Err(Error::AlreadyClaimed)
+            );
 
-            // Test with zero goal
-            let result = contract.create_campaign(
-                String::from("Test"),
-                String::from("Test"),
-                U256::zero(),
+            // A milestone released
+            set_block_timestamp(500000000);
+            let campaign_id = create_test_campaign(
+                &mut contract,
+                1000,
                 1000000000,
+                CampaignOptions {
+                    milestones: vec![U256::from(400), U256::from(600)],
+                    ..CampaignOptions::default()
+                },
+            );
+            contribute_as(&mut contract, campaign_id, Address::from([0x02; 20]), 1000);
+            set_caller(admin);
+            set_block_timestamp(1000000001);
+            assert_eq!(contract.finalize(campaign_id), Ok(()));
+            assert_eq!(contract.release_milestone(campaign_id, 0), Ok(()));
+            assert_eq!(
+                contract.enable_refunds(campaign_id),
+                Err(Error::AlreadyClaimed)
             );
-            assert_eq!(result, Err(Error::InvalidParameters));
 
-            // Test with past deadline
-            let result = contract.create_campaign(
-                String::from("Test"),
-                String::from("Test"),
-                U256::from(1000),
-                0, // Past timestamp
+            // A failed campaign is refundable already
+            let campaign_id = failed_campaign(&mut contract, &[]);
+            assert_eq!(
+                contract.enable_refunds(campaign_id),
+                Err(Error::InvalidParameters)
             );
-            assert_eq!(result, Err(Error::InvalidParameters));
         }
 
         #[ink::test]
-        fn get_campaign_not_found() {
+        fn contribution_history_records_each_contribution() {
             let token_address = Address::from([0x42; 20]);
-            let contract = InkFundMe::new(token_address);
-            let result = contract.get_campaign(0);
-            assert_eq!(result, Err(Error::CampaignNotFound));
+            let contributor = Address::from([0x02; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            assert!(
+                contract
+                    .get_contribution_history(campaign_id, contributor)
+                    .is_empty()
+            );
+
+            set_block_timestamp(500000100);
+            contribute_as(&mut contract, campaign_id, contributor, 300);
+            set_block_timestamp(500000200);
+            contribute_as(&mut contract, campaign_id, contributor, 200);
+
+            assert_eq!(
+                contract.get_contribution_history(campaign_id, contributor),
+                vec![(500000100, U256::from(300)), (500000200, U256::from(200))]
+            );
+            // The net amount is still tracked separately
+            assert_eq!(
+                contract.get_contribution(campaign_id, contributor),
+                U256::from(500)
+            );
         }
 
         #[ink::test]
-        fn get_contribution_works() {
+        fn contribution_history_is_bounded() {
             let token_address = Address::from([0x42; 20]);
-            let contract = InkFundMe::new(token_address);
-            let contributor = Address::from([0x01; 20]);
-            let contribution = contract.get_contribution(0, contributor);
-            assert_eq!(contribution, U256::zero());
+            let contributor = Address::from([0x02; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+
+            for i in 0..=MAX_CONTRIBUTION_HISTORY as u64 {
+                set_block_timestamp(500000000 + i);
+                contribute_as(&mut contract, campaign_id, contributor, 1);
+            }
+
+            // The oldest entry was dropped
+            let history = contract.get_contribution_history(campaign_id, contributor);
+            assert_eq!(history.len(), MAX_CONTRIBUTION_HISTORY);
+            assert_eq!(history[0], (500000001, U256::from(1)));
+            assert_eq!(
+                history[MAX_CONTRIBUTION_HISTORY - 1],
+                (500000000 + MAX_CONTRIBUTION_HISTORY as u64, U256::from(1))
+            );
         }
 
         #[ink::test]
-        #[should_panic(expected = "failed getting code hash")]
-        fn mint_faucet_cross_contract_call() {
+        fn get_token_total_supply_queries_token() {
             let token_address = Address::from([0x42; 20]);
-            let mut contract = InkFundMe::new(token_address);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            mock_token::set_balance(token_address, Address::from([0x01; 20]), U256::from(700));
+            assert_eq!(contract.get_token_total_supply(), U256::from(700));
 
-            // Set caller
-            let caller = Address::from([0x01; 20]);
-            set_caller(caller);
+            // Faucet mints add to the supply
+            set_caller(Address::from([0x02; 20]));
+            assert_eq!(contract.mint_faucet(U256::from(300)), Ok(()));
+            assert_eq!(contract.get_token_total_supply(), U256::from(1000));
+        }
 
-            // This test verifies that the mint_faucet function attempts a cross-contract call
-            // It will panic because the token address doesn't point to a real contract
-            let amount = U256::from(1000);
-            let _result = contract.mint_faucet(amount);
+        #[ink::test]
+        fn goal_met_and_refundable_predicates_work() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
+            let succeeded_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            let active_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            contribute_as(&mut contract, succeeded_id, Address::from([0x02; 20]), 1000);
+            contribute_as(&mut contract, active_id, Address::from([0x02; 20]), 1000);
 
-            // This line should not be reached due to the panic
+            // Active campaigns are never refundable, even with the goal met
+            assert_eq!(contract.is_goal_met(active_id), Ok(true));
+            assert_eq!(contract.is_refundable(active_id), Ok(false));
+
+            set_caller(owner);
+            set_block_timestamp(1000000001);
+            assert_eq!(contract.finalize(succeeded_id), Ok(()));
+            assert_eq!(contract.is_goal_met(succeeded_id), Ok(true));
+            assert_eq!(contract.is_refundable(succeeded_id), Ok(false));
+
+            let failed_id = failed_campaign(&mut contract, &[(Address::from([0x02; 20]), 300)]);
+            assert_eq!(contract.is_goal_met(failed_id), Ok(false));
+            assert_eq!(contract.is_refundable(failed_id), Ok(true));
+
+            assert_eq!(contract.is_goal_met(7), Err(Error::CampaignNotFound));
+            assert_eq!(contract.is_refundable(7), Err(Error::CampaignNotFound));
         }
 
         #[ink::test]
-        fn get_all_campaigns_works() {
+        fn remove_campaign_requires_empty_campaign_and_authorized_caller() {
             let token_address = Address::from([0x42; 20]);
-            let mut contract = InkFundMe::new(token_address);
+            let admin = Address::from([0x01; 20]);
+            let owner = Address::from([0x03; 20]);
+            let contributor = Address::from([0x02; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            set_block_timestamp(500000000);
 
-            // Initially no campaigns
-            let campaigns = contract.get_all_campaigns();
-            assert_eq!(campaigns.len(), 0);
+            set_caller(owner);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            contribute_as(&mut contract, campaign_id, contributor, 300);
 
-            // Set timestamp for campaign creation
+            assert_eq!(contract.remove_campaign(campaign_id), Err(Error::OnlyOwner));
+            set_caller(owner);
+            assert_eq!(
+                contract.remove_campaign(campaign_id),
+                Err(Error::CampaignNotEmpty)
+            );
+
+            // Empty again once the contribution was withdrawn
+            set_caller(contributor);
+            assert_eq!(
+                contract.withdraw_contribution(campaign_id, U256::from(300)),
+                Ok(())
+            );
+            set_caller(admin);
+            assert_eq!(contract.remove_campaign(campaign_id), Ok(()));
+            assert_eq!(contract.get_active_campaign_count(), 0);
+            assert_eq!(
+                contract.get_campaign(campaign_id),
+                Err(Error::CampaignNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn remove_campaign_keeps_completed_count_consistent() {
+            let token_address = Address::from([0x42; 20]);
+            let owner = Address::from([0x01; 20]);
+            let contributor = Address::from([0x02; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+
+            let campaign_id = failed_campaign(&mut contract, &[(contributor, 300)]);
+            assert_eq!(contract.get_completed_campaign_count(), 1);
+
+            set_caller(contributor);
+            assert_eq!(contract.claim_refund(campaign_id), Ok(()));
+            set_caller(owner);
+            assert_eq!(contract.remove_campaign(campaign_id), Ok(()));
+            assert_eq!(contract.get_completed_campaign_count(), 0);
+            assert_eq!(contract.get_active_campaign_count(), 0);
+        }
+
+        #[ink::test]
+        fn get_campaign_contributor_stats_works() {
+            let token_address = Address::from([0x42; 20]);
+            let first = Address::from([0x02; 20]);
+            let second = Address::from([0x03; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
             set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            assert_eq!(
+                contract.get_campaign_contributor_stats(campaign_id),
+                Ok((0, U256::zero()))
+            );
 
-            // Create a campaign
-            let result = contract.create_campaign(
-                String::from("Test Campaign"),
-                String::from("Description"),
-                U256::from(1000),
-                1000000000,
+            contribute_as(&mut contract, campaign_id, first, 100);
+            contribute_as(&mut contract, campaign_id, second, 300);
+            // Repeat contributions don't add contributors
+            contribute_as(&mut contract, campaign_id, first, 100);
+            assert_eq!(contract.get_contributor_count(campaign_id), Ok(2));
+            assert_eq!(
+                contract.get_campaign_contributor_stats(campaign_id),
+                Ok((2, U256::from(250)))
             );
-            assert!(result.is_ok());
 
-            // Now should have one campaign
-            let campaigns = contract.get_all_campaigns();
-            assert_eq!(campaigns.len(), 1);
-            assert_eq!(campaigns[0].title, "Test Campaign");
+            // A full withdrawal removes the contributor
+            set_caller(first);
+            assert_eq!(
+                contract.withdraw_contribution(campaign_id, U256::from(200)),
+                Ok(())
+            );
+            assert_eq!(
+                contract.get_campaign_contributor_stats(campaign_id),
+                Ok((1, U256::from(300)))
+            );
+
+            assert_eq!(
+                contract.get_campaign_contributor_stats(1),
+                Err(Error::CampaignNotFound)
+            );
         }
 
         #[ink::test]
-        fn multiple_campaigns_work() {
+        fn refunds_reduce_contributor_count() {
             let token_address = Address::from([0x42; 20]);
-            let mut contract = InkFundMe::new(token_address);
+            let first = Address::from([0x02; 20]);
+            let second = Address::from([0x03; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let campaign_id = failed_campaign(&mut contract, &[(first, 300), (second, 200)]);
+            assert_eq!(contract.get_contributor_count(campaign_id), Ok(2));
 
-            // Set timestamp
-            set_block_timestamp(500000000);
+            set_caller(first);
+            assert_eq!(contract.claim_refund(campaign_id), Ok(()));
+            assert_eq!(
+                contract.get_campaign_contributor_stats(campaign_id),
+                Ok((1, U256::from(200)))
+            );
+        }
 
-            // Create multiple campaigns
-            for i in 0..3 {
-                let result = contract.create_campaign(
-                    format!("Campaign {}", i),
-                    format!("Description {}", i),
-                    U256::from(1000 + i as u128),
-                    1000000000 + i as u64,
-                );
-                assert!(result.is_ok());
-                assert_eq!(result.unwrap(), i as u32);
-            }
+        #[ink::test]
+        fn set_token_contract_with_pending_refunds_fails() {
+            let token_address = Address::from([0x42; 20]);
+            let contributor = Address::from([0x02; 20]);
+            let mut contract = InkFundMe::new(token_address, Config::default());
+            let campaign_id = failed_campaign(&mut contract, &[(contributor, 300)]);
 
-            assert_eq!(contract.get_campaign_count(), 3);
+            // Finalized but the refund is still held by the contract
+            assert_eq!(
+                contract.set_token_contract(Address::from([0x43; 20])),
+                Err(Error::ActiveCampaignsExist)
+            );
 
-            // Check each campaign
-            for i in 0..3 {
-                let campaign = contract.get_campaign(i as u32).unwrap();
-                assert_eq!(campaign.title, format!("Campaign {}", i));
-                assert_eq!(campaign.goal, U256::from(1000 + i as u128));
+            set_caller(contributor);
+            assert_eq!(contract.claim_refund(campaign_id), Ok(()));
+
+            set_caller(Address::from([0x01; 20]));
+            assert_eq!(
+                contract.set_token_contract(Address::from([0x43; 20])),
+                Ok(())
+            );
+        }
+
+        #[ink::test]
+        fn claim_overfunding_refund_keeps_raised_in_sync() {
+            let owner = Address::from([0x01; 20]);
+            let first = Address::from([0x02; 20]);
+            let second = Address::from([0x03; 20]);
+            let token_address = Address::from([0x42; 20]);
+            set_caller(owner);
+            let mut contract = InkFundMe::new(token_address, refund_window_config());
+            let campaign_id = overfunded_campaign(&mut contract, first, second);
+
+            set_caller(first);
+            assert_eq!(contract.claim_overfunding_refund(campaign_id), Ok(()));
+            assert_eq!(
+                contract.get_campaign(campaign_id).unwrap().raised,
+                U256::from(1150)
+            );
+            // The second contributor's share is unaffected by the first claim
+            assert_eq!(
+                contract.get_overfunding_refund(campaign_id, second),
+                Ok(U256::from(150))
+            );
+
+            // Unwinding refunds what is left of each contribution
+            set_caller(owner);
+            assert_eq!(contract.enable_refunds(campaign_id), Ok(()));
+            for contributor in [first, second] {
+                set_caller(contributor);
+                assert_eq!(contract.claim_refund(campaign_id), Ok(()));
             }
+            assert_eq!(balance_of(token_address, first), U256::from(300));
+            assert_eq!(balance_of(token_address, second), U256::from(900));
+
+            // Nothing but dust is left to sweep
+            set_caller(owner);
+            set_block_timestamp(u64::MAX);
+            assert_eq!(contract.sweep_unclaimed(campaign_id), Ok(()));
+            assert_eq!(balance_of(token_address, owner), U256::zero());
+            assert_eq!(balance_of(token_address, contract_address()), U256::zero());
+            assert_eq!(contract.get_total_value_locked(), U256::zero());
+        }
+
+        #[ink::test]
+        fn enable_refunds_starts_refund_window() {
+            let token_address = Address::from([0x42; 20]);
+            let admin = Address::from([0x01; 20]);
+            let contributor = Address::from([0x02; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, refund_window_config());
+            set_block_timestamp(500000000);
+            let campaign_id =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            contribute_as(&mut contract, campaign_id, contributor, 1000);
+
+            // Unwound long after the contribution period's refund window
+            set_caller(admin);
+            set_block_timestamp(1000000001);
+            assert_eq!(contract.finalize(campaign_id), Ok(()));
+            set_block_timestamp(1000005000);
+            assert_eq!(contract.enable_refunds(campaign_id), Ok(()));
+
+            // Contributors still get the full window to claim
+            assert_eq!(
+                contract.sweep_unclaimed(campaign_id),
+                Err(Error::DeadlineNotReached)
+            );
+            set_block_timestamp(1000006000);
+            assert_eq!(
+                contract.sweep_unclaimed(campaign_id),
+                Err(Error::DeadlineNotReached)
+            );
+
+            set_block_timestamp(1000006001);
+            assert_eq!(contract.sweep_unclaimed(campaign_id), Ok(()));
+            assert_eq!(balance_of(token_address, admin), U256::from(1000));
+        }
+
+        #[ink::test]
+        fn new_with_token_hands_supply_and_ownership_to_deployer() {
+            let deployer = Address::from([0x01; 20]);
+            set_caller(deployer);
+            let contract = InkFundMe::new_with_token(
+                H256::zero(),
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                Config::default(),
+            )
+            .unwrap();
+
+            let token_address = contract.get_token_address();
+            assert_eq!(balance_of(token_address, deployer), U256::from(1000));
+            assert_eq!(balance_of(token_address, contract_address()), U256::zero());
+            assert_eq!(
+                TokenRef::from_addr(token_address).pending_owner(),
+                Some(deployer)
+            );
+            assert_eq!(
+                contract.get_token_metadata(),
+                (String::from("Test Token"), String::from("TEST"), 18)
+            );
+        }
+
+        #[ink::test]
+        fn sweep_unclaimed_empties_failed_campaign_only() {
+            let token_address = Address::from([0x42; 20]);
+            let admin = Address::from([0x01; 20]);
+            set_caller(admin);
+            let mut contract = InkFundMe::new(token_address, refund_window_config());
+
+            // Nothing to sweep from a successful campaign
+            set_block_timestamp(500000000);
+            let succeeded =
+                create_test_campaign(&mut contract, 1000, 1000000000, CampaignOptions::default());
+            contribute_as(&mut contract, succeeded, Address::from([0x02; 20]), 1000);
+            set_caller(admin);
+            set_block_timestamp(1000000001);
+            assert_eq!(contract.finalize(succeeded), Ok(()));
+            set_block_timestamp(1000001001);
+            assert_eq!(
+                contract.sweep_unclaimed(succeeded),
+                Err(Error::InvalidParameters)
+            );
+
+            let failed = failed_campaign(&mut contract, &[(Address::from([0x03; 20]), 300)]);
+            set_block_timestamp(1000001001);
+            assert_eq!(contract.sweep_unclaimed(failed), Ok(()));
+            assert_eq!(contract.get_campaign(failed).unwrap().raised, U256::zero());
+            assert_eq!(contract.get_total_value_locked(), U256::from(1000));
+        }
+    }
+
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::*;
+        use ink_e2e::ContractsBackend;
+        use token::{Token, TokenRef};
+
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        #[ink_e2e::test]
+        async fn new_checked_caches_token_metadata<Client: E2EBackend>(
+            mut client: Client,
+        ) -> E2EResult<()> {
+            // Given
+            let mut token_constructor = TokenRef::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            let token = client
+                .instantiate("token", &ink_e2e::alice(), &mut token_constructor)
+                .submit()
+                .await
+                .expect("token instantiate failed");
+
+            let mut constructor = InkFundMeRef::new_checked(token.addr, Config::default());
+            let contract = client
+                .instantiate("inkfundme", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("inkfundme instantiate failed");
+            let call_builder = contract.call_builder::<InkFundMe>();
+
+            // When
+            let metadata = client
+                .call(&ink_e2e::alice(), &call_builder.get_token_metadata())
+                .dry_run()
+                .await?
+                .return_value();
+
+            // Then
+            assert_eq!(
+                metadata,
+                (String::from("Test Token"), String::from("TEST"), 18)
+            );
+
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn new_works<Client: E2EBackend>(mut client: Client) -> E2EResult<()> {
+            // Given
+            let token_code = client
+                .upload("token", &ink_e2e::alice())
+                .submit()
+                .await
+                .expect("token upload failed");
+
+            let mut constructor = InkFundMeRef::new_with_token(
+                token_code.code_hash,
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                Config::default(),
+            );
+            let contract = client
+                .instantiate("inkfundme", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("inkfundme instantiate failed");
+            let call_builder = contract.call_builder::<InkFundMe>();
+
+            // When
+            let token_address = client
+                .call(&ink_e2e::alice(), &call_builder.get_token_address())
+                .dry_run()
+                .await?
+                .return_value();
+            let metadata = client
+                .call(&ink_e2e::alice(), &call_builder.get_token_metadata())
+                .dry_run()
+                .await?
+                .return_value();
+            let campaign_count = client
+                .call(&ink_e2e::alice(), &call_builder.get_campaign_count())
+                .dry_run()
+                .await?
+                .return_value();
+
+            // Then
+            assert_ne!(token_address, Address::zero());
+            assert_eq!(
+                metadata,
+                (String::from("Test Token"), String::from("TEST"), 18)
+            );
+            assert_eq!(campaign_count, 0);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn contract_token_balance_tracks_contributions<Client: E2EBackend>(
+            mut client: Client,
+        ) -> E2EResult<()> {
+            // Given
+            let mut token_constructor = TokenRef::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            let token = client
+                .instantiate("token", &ink_e2e::alice(), &mut token_constructor)
+                .submit()
+                .await
+                .expect("token instantiate failed");
+            let mut token_call_builder = token.call_builder::<Token>();
+
+            let mut constructor = InkFundMeRef::new(token.addr, Config::default());
+            let contract = client
+                .instantiate("inkfundme", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("inkfundme instantiate failed");
+            let mut call_builder = contract.call_builder::<InkFundMe>();
+
+            let amount = U256::from(300);
+            client
+                .call(
+                    &ink_e2e::alice(),
+                    &token_call_builder.approve(contract.addr, amount),
+                )
+                .submit()
+                .await
+                .expect("approve failed");
+            client
+                .call(
+                    &ink_e2e::alice(),
+                    &call_builder.create_campaign(
+                        String::from("Test Campaign"),
+                        String::from("Description"),
+                        U256::from(1000),
+                        u64::MAX,
+                        CampaignOptions::default(),
+                    ),
+                )
+                .submit()
+                .await
+                .expect("create_campaign failed");
+
+            // When
+            client
+                .call(&ink_e2e::alice(), &call_builder.contribute(0, amount))
+                .submit()
+                .await
+                .expect("contribute failed");
+
+            // Then
+            let balance = client
+                .call(
+                    &ink_e2e::alice(),
+                    &call_builder.get_contract_token_balance(),
+                )
+                .dry_run()
+                .await?
+                .return_value();
+            let locked = client
+                .call(&ink_e2e::alice(), &call_builder.get_total_value_locked())
+                .dry_run()
+                .await?
+                .return_value();
+            assert_eq!(balance, amount);
+            assert_eq!(locked, amount);
+
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn contribute_preflight_reports_missing_funds<Client: E2EBackend>(
+            mut client: Client,
+        ) -> E2EResult<()> {
+            // Given
+            let mut token_constructor = TokenRef::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            let token = client
+                .instantiate("token", &ink_e2e::alice(), &mut token_constructor)
+                .submit()
+                .await
+                .expect("token instantiate failed");
+            let mut token_call_builder = token.call_builder::<Token>();
+
+            let mut constructor = InkFundMeRef::new(token.addr, Config::default());
+            let contract = client
+                .instantiate("inkfundme", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("inkfundme instantiate failed");
+            let mut call_builder = contract.call_builder::<InkFundMe>();
+
+            client
+                .call(
+                    &ink_e2e::alice(),
+                    &call_builder.create_campaign(
+                        String::from("Test Campaign"),
+                        String::from("Description"),
+                        U256::from(1000),
+                        u64::MAX,
+                        CampaignOptions::default(),
+                    ),
+                )
+                .submit()
+                .await
+                .expect("create_campaign failed");
+            let amount = U256::from(300);
+
+            // When / Then: nothing approved yet
+            let result = client
+                .call(&ink_e2e::alice(), &call_builder.contribute(0, amount))
+                .dry_run()
+                .await?
+                .return_value();
+            assert_eq!(result, Err(Error::InsufficientAllowance));
+
+            // When / Then: approved, but bob holds no tokens
+            client
+                .call(
+                    &ink_e2e::bob(),
+                    &token_call_builder.approve(contract.addr, amount),
+                )
+                .submit()
+                .await
+                .expect("approve failed");
+            let result = client
+                .call(&ink_e2e::bob(), &call_builder.contribute(0, amount))
+                .dry_run()
+                .await?
+                .return_value();
+            assert_eq!(result, Err(Error::InsufficientTokenBalance));
+
+            // When / Then: approved and funded
+            client
+                .call(
+                    &ink_e2e::alice(),
+                    &token_call_builder.approve(contract.addr, amount),
+                )
+                .submit()
+                .await
+                .expect("approve failed");
+            let result = client
+                .call(&ink_e2e::alice(), &call_builder.contribute(0, amount))
+                .submit()
+                .await
+                .expect("contribute failed")
+                .return_value();
+            assert_eq!(result, Ok(()));
+
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn campaign_token_receives_contributions<Client: E2EBackend>(
+            mut client: Client,
+        ) -> E2EResult<()> {
+            // Given
+            let mut default_constructor = TokenRef::new(
+                String::from("Default Token"),
+                String::from("DEF"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            let default_token = client
+                .instantiate("token", &ink_e2e::alice(), &mut default_constructor)
+                .submit()
+                .await
+                .expect("default token instantiate failed");
+            let default_call_builder = default_token.call_builder::<Token>();
+
+            let mut other_constructor = TokenRef::new(
+                String::from("Other Token"),
+                String::from("OTH"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            let other_token = client
+                .instantiate("token", &ink_e2e::alice(), &mut other_constructor)
+                .submit()
+                .await
+                .expect("other token instantiate failed");
+            let mut other_call_builder = other_token.call_builder::<Token>();
+
+            let mut constructor = InkFundMeRef::new(default_token.addr, Config::default());
+            let contract = client
+                .instantiate("inkfundme", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("inkfundme instantiate failed");
+            let mut call_builder = contract.call_builder::<InkFundMe>();
+
+            let amount = U256::from(300);
+            client
+                .call(
+                    &ink_e2e::alice(),
+                    &other_call_builder.approve(contract.addr, amount),
+                )
+                .submit()
+                .await
+                .expect("approve failed");
+            client
+                .call(
+                    &ink_e2e::alice(),
+                    &call_builder.create_campaign(
+                        String::from("Test Campaign"),
+                        String::from("Description"),
+                        U256::from(1000),
+                        u64::MAX,
+                        CampaignOptions {
+                            token: Some(other_token.addr),
+                            ..CampaignOptions::default()
+                        },
+                    ),
+                )
+                .submit()
+                .await
+                .expect("create_campaign failed");
+
+            // When
+            client
+                .call(&ink_e2e::alice(), &call_builder.contribute(0, amount))
+                .submit()
+                .await
+                .expect("contribute failed");
+
+            // Then
+            let other_balance = client
+                .call(
+                    &ink_e2e::alice(),
+                    &other_call_builder.balance_of(contract.addr),
+                )
+                .dry_run()
+                .await?
+                .return_value();
+            let default_balance = client
+                .call(
+                    &ink_e2e::alice(),
+                    &default_call_builder.balance_of(contract.addr),
+                )
+                .dry_run()
+                .await?
+                .return_value();
+            let campaign_token = client
+                .call(&ink_e2e::alice(), &call_builder.get_campaign_token(0))
+                .dry_run()
+                .await?
+                .return_value();
+            assert_eq!(other_balance, amount);
+            assert_eq!(default_balance, U256::zero());
+            assert_eq!(campaign_token, Ok(other_token.addr));
+
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn required_allowance_reports_shortfall<Client: E2EBackend>(
+            mut client: Client,
+        ) -> E2EResult<()> {
+            // Given
+            let mut token_constructor = TokenRef::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            let token = client
+                .instantiate("token", &ink_e2e::alice(), &mut token_constructor)
+                .submit()
+                .await
+                .expect("token instantiate failed");
+            let mut token_call_builder = token.call_builder::<Token>();
+
+            let mut constructor = InkFundMeRef::new(token.addr, Config::default());
+            let contract = client
+                .instantiate("inkfundme", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("inkfundme instantiate failed");
+            let mut call_builder = contract.call_builder::<InkFundMe>();
+
+            client
+                .call(
+                    &ink_e2e::alice(),
+                    &call_builder.create_campaign(
+                        String::from("Test Campaign"),
+                        String::from("Description"),
+                        U256::from(1000),
+                        u64::MAX,
+                        CampaignOptions::default(),
+                    ),
+                )
+                .submit()
+                .await
+                .expect("create_campaign failed");
+            client
+                .call(
+                    &ink_e2e::alice(),
+                    &token_call_builder.approve(contract.addr, U256::from(300)),
+                )
+                .submit()
+                .await
+                .expect("approve failed");
+            let alice =
+                ink_e2e::address::<ink::env::DefaultEnvironment>(ink_e2e::Sr25519Keyring::Alice);
+
+            // When / Then: more than approved
+            let shortfall = client
+                .call(
+                    &ink_e2e::alice(),
+                    &call_builder.required_allowance(0, alice, U256::from(500)),
+                )
+                .dry_run()
+                .await?
+                .return_value();
+            assert_eq!(shortfall, Ok(U256::from(200)));
+
+            // When / Then: covered by the allowance
+            let shortfall = client
+                .call(
+                    &ink_e2e::alice(),
+                    &call_builder.required_allowance(0, alice, U256::from(300)),
+                )
+                .dry_run()
+                .await?
+                .return_value();
+            assert_eq!(shortfall, Ok(U256::zero()));
+
+            Ok(())
+        }
+
+        #[ink_e2e::test]
+        async fn transfer_and_call_contributes_for_sender<Client: E2EBackend>(
+            mut client: Client,
+        ) -> E2EResult<()> {
+            // Given
+            let mut token_constructor = TokenRef::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            let token = client
+                .instantiate("token", &ink_e2e::alice(), &mut token_constructor)
+                .submit()
+                .await
+                .expect("token instantiate failed");
+            let mut token_call_builder = token.call_builder::<Token>();
+
+            let mut constructor = InkFundMeRef::new(token.addr, Config::default());
+            let contract = client
+                .instantiate("inkfundme", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("inkfundme instantiate failed");
+            let mut call_builder = contract.call_builder::<InkFundMe>();
+
+            client
+                .call(
+                    &ink_e2e::bob(),
+                    &call_builder.create_campaign(
+                        String::from("Test Campaign"),
+                        String::from("Description"),
+                        U256::from(1000),
+                        u64::MAX,
+                        CampaignOptions::default(),
+                    ),
+                )
+                .submit()
+                .await
+                .expect("create_campaign failed");
+
+            // When
+            let amount = U256::from(300);
+            let result = client
+                .call(
+                    &ink_e2e::alice(),
+                    &token_call_builder.transfer_and_call(
+                        contract.addr,
+                        amount,
+                        0u32.to_le_bytes().to_vec(),
+                    ),
+                )
+                .submit()
+                .await
+                .expect("transfer_and_call failed")
+                .return_value();
+            assert_eq!(result, Ok(()));
+
+            // Then: calling the receiver directly credits nothing
+            let alice =
+                ink_e2e::address::<ink::env::DefaultEnvironment>(ink_e2e::Sr25519Keyring::Alice);
+            let forged = client
+                .call(
+                    &ink_e2e::bob(),
+                    &call_builder.on_token_transfer(alice, amount, 0u32.to_le_bytes().to_vec()),
+                )
+                .submit()
+                .await;
+            assert!(forged.is_err());
+
+            // Then: an unknown campaign reverts the transfer
+            let result = client
+                .call(
+                    &ink_e2e::alice(),
+                    &token_call_builder.transfer_and_call(
+                        contract.addr,
+                        amount,
+                        7u32.to_le_bytes().to_vec(),
+                    ),
+                )
+                .dry_run()
+                .await?
+                .return_value();
+            assert_eq!(result, Err(token::Error::CallbackFailed));
+
+            let contribution = client
+                .call(&ink_e2e::alice(), &call_builder.get_contribution(0, alice))
+                .dry_run()
+                .await?
+                .return_value();
+            let locked = client
+                .call(&ink_e2e::alice(), &call_builder.get_total_value_locked())
+                .dry_run()
+                .await?
+                .return_value();
+            let balance = client
+                .call(
+                    &ink_e2e::alice(),
+                    &token_call_builder.balance_of(contract.addr),
+                )
+                .dry_run()
+                .await?
+                .return_value();
+            assert_eq!(contribution, amount);
+            assert_eq!(locked, amount);
+            assert_eq!(balance, amount);
+
+            Ok(())
+        }
+
+        /// E2E callers are sr25519 accounts, which can't sign secp256k1 permits
+        /// for their own address; the signed happy path is covered by the unit
+        /// tests, whose token mock verifies signatures like the real token
+        #[ink_e2e::test]
+        async fn contribute_with_permit_checks_signature_in_token<Client: E2EBackend>(
+            mut client: Client,
+        ) -> E2EResult<()> {
+            // Given
+            let mut token_constructor = TokenRef::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            let token = client
+                .instantiate("token", &ink_e2e::alice(), &mut token_constructor)
+                .submit()
+                .await
+                .expect("token instantiate failed");
+            let token_call_builder = token.call_builder::<Token>();
+
+            let mut constructor = InkFundMeRef::new(token.addr, Config::default());
+            let contract = client
+                .instantiate("inkfundme", &ink_e2e::alice(), &mut constructor)
+                .submit()
+                .await
+                .expect("inkfundme instantiate failed");
+            let mut call_builder = contract.call_builder::<InkFundMe>();
+
+            client
+                .call(
+                    &ink_e2e::bob(),
+                    &call_builder.create_campaign(
+                        String::from("Test Campaign"),
+                        String::from("Description"),
+                        U256::from(1000),
+                        u64::MAX,
+                        CampaignOptions::default(),
+                    ),
+                )
+                .submit()
+                .await
+                .expect("create_campaign failed");
+            let alice =
+                ink_e2e::address::<ink::env::DefaultEnvironment>(ink_e2e::Sr25519Keyring::Alice);
+            let amount = U256::from(300);
+
+            // A well-formed permit for alice's tokens, signed by a key that isn't
+            // alice's
+            let message_hash = client
+                .call(
+                    &ink_e2e::alice(),
+                    &token_call_builder.permit_hash(alice, contract.addr, amount, 0, u64::MAX),
+                )
+                .dry_run()
+                .await?
+                .return_value();
+            let secp = secp256k1::Secp256k1::new();
+            let secret_key = secp256k1::SecretKey::from_byte_array(&[0x11; 32]).unwrap();
+            let message = secp256k1::Message::from_digest(message_hash);
+            let (recovery_id, compact) = secp
+                .sign_ecdsa_recoverable(&message, &secret_key)
+                .serialize_compact();
+            let mut signature = [0u8; 65];
+            signature[..64].copy_from_slice(&compact);
+            signature[64] = i32::from(recovery_id) as u8;
+
+            // When
+            let result = client
+                .call(
+                    &ink_e2e::alice(),
+                    &call_builder.contribute_with_permit(0, amount, u64::MAX, signature),
+                )
+                .dry_run()
+                .await?
+                .return_value();
+
+            // Then
+            assert_eq!(
+                result,
+                Err(Error::TokenError(token::Error::InvalidSignature))
+            );
+            let allowance = client
+                .call(
+                    &ink_e2e::alice(),
+                    &token_call_builder.allowance(alice, contract.addr),
+                )
+                .dry_run()
+                .await?
+                .return_value();
+            assert_eq!(allowance, U256::zero());
+
+            Ok(())
         }
     }
 }
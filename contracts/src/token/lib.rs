@@ -4,7 +4,42 @@ pub use token::*;
 
 #[ink::contract]
 pub mod token {
-    use ink::{prelude::string::String, storage::Mapping, U256};
+    use ink::{
+        env::{
+            call::{build_call, ExecutionInput, Selector},
+            hash::Keccak256,
+        },
+        prelude::{string::String, vec::Vec},
+        storage::Mapping,
+        U256,
+    };
+
+    /// Domain tag mixed into every permit hash so signatures cannot be replayed
+    /// against other message formats
+    pub const PERMIT_DOMAIN: &[u8] = b"InkFundMe Token Permit";
+
+    /// Selector of the `on_token_transfer(from: Address, value: U256, data: Vec<u8>)`
+    /// message `transfer_and_call` invokes on contract recipients
+    const ON_TOKEN_TRANSFER_SELECTOR: [u8; 4] = ink::selector_bytes!("on_token_transfer");
+
+    /// Maximum length of the token name in bytes
+    pub const MAX_NAME_LEN: usize = 64;
+
+    /// Maximum length of the token symbol in bytes
+    pub const MAX_SYMBOL_LEN: usize = 12;
+
+    /// Maximum number of token decimals, larger values are clamped to it
+    pub const MAX_DECIMALS: u8 = 36;
+
+    /// Maximum number of entries answered by a single batch query
+    pub const MAX_BATCH_LEN: usize = 100;
+
+    /// Maximum length of a transfer memo in bytes
+    pub const MAX_MEMO_LEN: usize = 64;
+
+    /// Version of the contract code, bumped on every release changing its
+    /// interface or storage layout
+    pub const CONTRACT_VERSION: u32 = 1;
 
     /// InkFundMe ERC20 Token with minting capabilities
     #[ink(storage)]
@@ -17,12 +52,40 @@ pub mod token {
         /// Mapping of the token amount which an account is allowed to withdraw
         /// from another account
         allowances: Mapping<(Address, Address), U256>,
+        /// Mapping from `(owner, spender)` to the timestamp after which their
+        /// allowance counts as zero, absent for allowances that never expire
+        allowance_expiries: Mapping<(Address, Address), u64>,
         /// Token name
         name: String,
         /// Token symbol
         symbol: String,
         /// Token decimals
         decimals: u8,
+        /// Mapping from owner to the nonce their next permit must be signed with
+        nonces: Mapping<Address, u64>,
+        /// Account allowed to perform privileged operations such as snapshots,
+        /// `None` once ownership was renounced
+        owner: Option<Address>,
+        /// Account the owner offered ownership to, until it accepts
+        pending_owner: Option<Address>,
+        /// Id of the most recent snapshot, `0` if no snapshot was taken yet
+        current_snapshot_id: u32,
+        /// Mapping from `(account, index)` to `(snapshot_id, balance)` entries in
+        /// ascending id order, each holding the balance the account had at that
+        /// snapshot. Stored one entry per cell so a long history costs nothing
+        /// to extend
+        balance_snapshots: Mapping<(Address, u32), (u32, U256)>,
+        /// Mapping from account to its number of `balance_snapshots` entries
+        balance_snapshot_counts: Mapping<Address, u32>,
+        /// `CONTRACT_VERSION` the storage was laid out with, for migrations to
+        /// branch on
+        schema_version: u32,
+        /// Largest allowance that can be granted, `None` for no limit
+        max_approval: Option<U256>,
+        /// Tokens ever minted, including the initial supply
+        total_minted: U256,
+        /// Tokens ever burned
+        total_burned: U256,
     }
 
     /// Event emitted when a token transfer occurs
@@ -47,6 +110,10 @@ pub mod token {
     }
 
     /// Event emitted when tokens are minted
+    ///
+    /// Always accompanies a `Transfer` from `None`, which is the event carrying
+    /// the supply change; indexers summing supply must not count both. Use
+    /// `total_minted` and `total_burned` as the authoritative totals
     #[ink(event)]
     pub struct Mint {
         #[ink(topic)]
@@ -54,6 +121,56 @@ pub mod token {
         value: U256,
     }
 
+    /// Event emitted when a balance snapshot is taken
+    #[ink(event)]
+    pub struct Snapshot {
+        #[ink(topic)]
+        id: u32,
+    }
+
+    /// Event emitted alongside `Transfer` when a transfer carries a memo
+    #[ink(event)]
+    pub struct TransferWithMemo {
+        #[ink(topic)]
+        from: Address,
+        #[ink(topic)]
+        to: Address,
+        value: U256,
+        memo: Vec<u8>,
+    }
+
+    /// Event emitted when the token name or symbol is changed
+    #[ink(event)]
+    pub struct MetadataUpdated {
+        name: String,
+        symbol: String,
+    }
+
+    /// Event emitted when the owner gives up ownership for good
+    #[ink(event)]
+    pub struct OwnershipRenounced {
+        #[ink(topic)]
+        previous_owner: Address,
+    }
+
+    /// Event emitted when the owner offers ownership to another account
+    #[ink(event)]
+    pub struct OwnershipTransferStarted {
+        #[ink(topic)]
+        previous_owner: Address,
+        #[ink(topic)]
+        new_owner: Address,
+    }
+
+    /// Event emitted when the pending owner accepts ownership
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        previous_owner: Option<Address>,
+        #[ink(topic)]
+        new_owner: Address,
+    }
+
     /// The ERC-20 error types
     #[derive(Debug, PartialEq, Eq)]
     #[ink::scale_derive(Encode, Decode, TypeInfo)]
@@ -64,6 +181,45 @@ pub mod token {
         InsufficientAllowance,
         /// Returned when trying to mint would cause overflow
         Overflow,
+        /// Returned if a permit signature was not produced by the token owner
+        InvalidSignature,
+        /// Returned if a permit is submitted after its deadline
+        PermitExpired,
+        /// Returned if the caller is not allowed to perform the operation
+        NotAuthorized,
+        /// Returned if a recipient contract rejected a `transfer_and_call`
+        CallbackFailed,
+        /// Returned if a new name or symbol is empty or too long
+        InvalidMetadata,
+        /// Returned if a transfer memo is longer than `MAX_MEMO_LEN` bytes
+        MemoTooLong,
+        /// Returned if an allowance would exceed the token's `max_approval`
+        ApprovalTooLarge,
+        /// Returned if a batch query holds more than `MAX_BATCH_LEN` entries
+        BatchTooLarge,
+    }
+
+    impl Error {
+        /// Returns a stable numeric code for the error, for clients that can't
+        /// decode the enum
+        ///
+        /// Codes start at `1` and follow declaration order; they never change once
+        /// assigned
+        pub fn code(&self) -> u32 {
+            match self {
+                Error::InsufficientBalance => 1,
+                Error::InsufficientAllowance => 2,
+                Error::Overflow => 3,
+                Error::InvalidSignature => 4,
+                Error::PermitExpired => 5,
+                Error::NotAuthorized => 6,
+                Error::CallbackFailed => 7,
+                Error::InvalidMetadata => 8,
+                Error::MemoTooLong => 9,
+                Error::ApprovalTooLarge => 10,
+                Error::BatchTooLarge => 11,
+            }
+        }
     }
 
     /// The ERC-20 result type
@@ -73,12 +229,23 @@ pub mod token {
         /// Creates a new InkFundMe ERC-20 token contract
         ///
         /// # Parameters
-        /// - `name`: Token name (e.g., "InkFundMe Token")
-        /// - `symbol`: Token symbol (e.g., "IFM")
-        /// - `decimals`: Number of decimals (typically 18)
+        /// - `name`: Token name (e.g., "InkFundMe Token"), truncated to
+        ///   `MAX_NAME_LEN` bytes
+        /// - `symbol`: Token symbol (e.g., "IFM"), truncated to `MAX_SYMBOL_LEN`
+        ///   bytes
+        /// - `decimals`: Number of decimals (typically 18), clamped to
+        ///   `MAX_DECIMALS`
         /// - `initial_supply`: Initial token supply (will be minted to deployer)
+        /// - `max_approval`: Largest allowance `approve` and `permit` can grant,
+        ///   `None` for no limit
         #[ink(constructor)]
-        pub fn new(name: String, symbol: String, decimals: u8, initial_supply: U256) -> Self {
+        pub fn new(
+            name: String,
+            symbol: String,
+            decimals: u8,
+            initial_supply: U256,
+            max_approval: Option<U256>,
+        ) -> Self {
             let mut balances = Mapping::default();
             let caller = Self::env().caller();
 
@@ -95,9 +262,20 @@ pub mod token {
                 total_supply: initial_supply,
                 balances,
                 allowances: Default::default(),
-                name,
-                symbol,
-                decimals,
+                allowance_expiries: Default::default(),
+                name: Self::truncated(name, MAX_NAME_LEN),
+                symbol: Self::truncated(symbol, MAX_SYMBOL_LEN),
+                decimals: decimals.min(MAX_DECIMALS),
+                nonces: Default::default(),
+                owner: Some(caller),
+                pending_owner: None,
+                current_snapshot_id: 0,
+                balance_snapshots: Default::default(),
+                balance_snapshot_counts: Default::default(),
+                schema_version: CONTRACT_VERSION,
+                max_approval,
+                total_minted: initial_supply,
+                total_burned: U256::zero(),
             }
         }
 
@@ -119,12 +297,114 @@ pub mod token {
             self.decimals
         }
 
+        /// Returns `10^decimals`, the number of base units in one whole token
+        ///
+        /// # Errors
+        ///
+        /// Returns `Overflow` error if `10^decimals` doesn't fit in a `U256`, which
+        /// can't happen while decimals are capped at `MAX_DECIMALS`
+        #[ink(message)]
+        pub fn scaling_factor(&self) -> Result<U256> {
+            U256::from(10)
+                .checked_pow(U256::from(self.decimals))
+                .ok_or(Error::Overflow)
+        }
+
+        /// Returns the version of the contract code
+        #[ink(message)]
+        pub fn get_version(&self) -> u32 {
+            CONTRACT_VERSION
+        }
+
+        /// Returns the largest allowance that can be granted, `None` for no limit
+        #[ink(message)]
+        pub fn max_approval(&self) -> Option<U256> {
+            self.max_approval
+        }
+
+        /// Changes the token name
+        ///
+        /// A `MetadataUpdated` event is emitted
+        ///
+        /// # Errors
+        ///
+        /// Returns `NotAuthorized` error if the caller is not the token owner
+        ///
+        /// Returns `InvalidMetadata` error if `name` is empty or longer than
+        /// `MAX_NAME_LEN` bytes
+        #[ink(message)]
+        pub fn set_name(&mut self, name: String) -> Result<()> {
+            self.ensure_owner()?;
+            if name.is_empty() || name.len() > MAX_NAME_LEN {
+                return Err(Error::InvalidMetadata);
+            }
+
+            self.name = name;
+            self.emit_metadata_updated();
+            Ok(())
+        }
+
+        /// Changes the token symbol
+        ///
+        /// A `MetadataUpdated` event is emitted
+        ///
+        /// # Errors
+        ///
+        /// Returns `NotAuthorized` error if the caller is not the token owner
+        ///
+        /// Returns `InvalidMetadata` error if `symbol` is empty or longer than
+        /// `MAX_SYMBOL_LEN` bytes
+        #[ink(message)]
+        pub fn set_symbol(&mut self, symbol: String) -> Result<()> {
+            self.ensure_owner()?;
+            if symbol.is_empty() || symbol.len() > MAX_SYMBOL_LEN {
+                return Err(Error::InvalidMetadata);
+            }
+
+            self.symbol = symbol;
+            self.emit_metadata_updated();
+            Ok(())
+        }
+
+        /// Cuts `value` down to at most `max_len` bytes, on a character boundary
+        fn truncated(mut value: String, max_len: usize) -> String {
+            if value.len() > max_len {
+                let mut end = max_len;
+                while !value.is_char_boundary(end) {
+                    end -= 1;
+                }
+                value.truncate(end);
+            }
+            value
+        }
+
+        /// Emits a `MetadataUpdated` event with the current name and symbol
+        fn emit_metadata_updated(&self) {
+            self.env().emit_event(MetadataUpdated {
+                name: self.name.clone(),
+                symbol: self.symbol.clone(),
+            });
+        }
+
         /// Returns the total token supply
         #[ink(message)]
         pub fn total_supply(&self) -> U256 {
             self.total_supply
         }
 
+        /// Returns the total amount of tokens ever minted, including the initial
+        /// supply
+        #[ink(message)]
+        pub fn total_minted(&self) -> U256 {
+            self.total_minted
+        }
+
+        /// Returns the total amount of tokens ever burned
+        #[ink(message)]
+        pub fn total_burned(&self) -> U256 {
+            self.total_burned
+        }
+
         /// Returns the account balance for the specified `owner`
         ///
         /// Returns `0` if the account is non-existent
@@ -133,6 +413,26 @@ pub mod token {
             self.balance_of_impl(&owner)
         }
 
+        /// Returns the balance of each of `owners`, in input order
+        ///
+        /// Unknown accounts have a balance of `0`
+        ///
+        /// # Errors
+        ///
+        /// Returns `BatchTooLarge` error if more than `MAX_BATCH_LEN` owners are
+        /// given
+        #[ink(message)]
+        pub fn balances_of(&self, owners: Vec<Address>) -> Result<Vec<U256>> {
+            if owners.len() > MAX_BATCH_LEN {
+                return Err(Error::BatchTooLarge);
+            }
+
+            Ok(owners
+                .iter()
+                .map(|owner| self.balance_of_impl(owner))
+                .collect())
+        }
+
         /// Returns the account balance for the specified `owner`
         ///
         /// Returns `0` if the account is non-existent
@@ -146,17 +446,121 @@ pub mod token {
             self.balances.get(owner).unwrap_or_default()
         }
 
+        /// Takes a snapshot of all balances and returns its id
+        ///
+        /// Balances are not copied; instead the first write to an account after a
+        /// snapshot records its previous balance, so history is only stored for
+        /// accounts that change while a snapshot is active
+        ///
+        /// A `Snapshot` event is emitted
+        ///
+        /// # Errors
+        ///
+        /// Returns `NotAuthorized` error if the caller is not the token owner
+        ///
+        /// Returns `Overflow` error if the snapshot id space is exhausted
+        #[ink(message)]
+        pub fn snapshot(&mut self) -> Result<u32> {
+            self.ensure_owner()?;
+
+            let id = self
+                .current_snapshot_id
+                .checked_add(1)
+                .ok_or(Error::Overflow)?;
+            self.current_snapshot_id = id;
+            self.env().emit_event(Snapshot { id });
+            Ok(id)
+        }
+
+        /// Returns the id of the most recent snapshot, `0` if none was taken
+        #[ink(message)]
+        pub fn current_snapshot_id(&self) -> u32 {
+            self.current_snapshot_id
+        }
+
+        /// Returns the balance `owner` had when snapshot `snapshot_id` was taken
+        ///
+        /// Returns `0` for a snapshot id that has not been taken yet
+        #[ink(message)]
+        pub fn balance_of_at(&self, owner: Address, snapshot_id: u32) -> U256 {
+            if snapshot_id == 0 || snapshot_id > self.current_snapshot_id {
+                return U256::zero();
+            }
+
+            // The first entry recorded at or after `snapshot_id` holds the balance at
+            // that snapshot; without one the balance has not changed since. Binary
+            // search, reading one entry per step
+            let (mut low, mut high) = (0, self.balance_snapshot_counts.get(owner).unwrap_or(0));
+            let mut found = None;
+            while low < high {
+                let mid = low + (high - low) / 2;
+                let entry = self.balance_snapshots.get((owner, mid)).unwrap_or_default();
+                if entry.0 < snapshot_id {
+                    low = mid + 1;
+                } else {
+                    found = Some(entry.1);
+                    high = mid;
+                }
+            }
+            found.unwrap_or_else(|| self.balance_of_impl(&owner))
+        }
+
+        /// Records the current balance of `account` under the active snapshot
+        ///
+        /// Must be called before every balance change. Does nothing if no snapshot
+        /// was taken or the balance was already recorded for the active snapshot
+        fn update_balance_snapshot(&mut self, account: &Address) {
+            let snapshot_id = self.current_snapshot_id;
+            if snapshot_id == 0 {
+                return;
+            }
+
+            let count = self.balance_snapshot_counts.get(account).unwrap_or(0);
+            if count > 0
+                && self
+                    .balance_snapshots
+                    .get((*account, count - 1))
+                    .is_some_and(|(id, _)| id == snapshot_id)
+            {
+                return;
+            }
+            self.balance_snapshots.insert(
+                (*account, count),
+                &(snapshot_id, self.balance_of_impl(account)),
+            );
+            self.balance_snapshot_counts
+                .insert(account, &count.saturating_add(1));
+        }
+
         /// Returns the amount which `spender` is still allowed to withdraw from `owner`
         ///
-        /// Returns `0` if no allowance has been set
+        /// Returns `0` if no allowance has been set or it expired
         #[ink(message)]
         pub fn allowance(&self, owner: Address, spender: Address) -> U256 {
             self.allowance_impl(&owner, &spender)
         }
 
+        /// Returns the allowance of each `(owner, spender)` pair, in input order
+        ///
+        /// # Errors
+        ///
+        /// Returns `BatchTooLarge` error if more than `MAX_BATCH_LEN` pairs are
+        /// given
+        #[ink(message)]
+        pub fn allowances_of(&self, pairs: Vec<(Address, Address)>) -> Result<Vec<U256>> {
+            if pairs.len() > MAX_BATCH_LEN {
+                return Err(Error::BatchTooLarge);
+            }
+
+            Ok(pairs
+                .iter()
+                .map(|(owner, spender)| self.allowance_impl(owner, spender))
+                .collect())
+        }
+
         /// Returns the amount which `spender` is still allowed to withdraw from `owner`
         ///
-        /// Returns `0` if no allowance has been set
+        /// Returns `0` if no allowance has been set or it expired
         ///
         /// # Note
         ///
@@ -164,9 +568,23 @@ pub mod token {
         /// works using references which are more efficient
         #[inline]
         fn allowance_impl(&self, owner: &Address, spender: &Address) -> U256 {
+            let expired = self
+                .allowance_expiries
+                .get((owner, spender))
+                .is_some_and(|expiry| self.env().block_timestamp() > expiry);
+            if expired {
+                return U256::zero();
+            }
             self.allowances.get((owner, spender)).unwrap_or_default()
         }
 
+        /// Returns the timestamp after which the allowance of `spender` over the
+        /// tokens of `owner` expires, `None` if it never does
+        #[ink(message)]
+        pub fn allowance_expiry(&self, owner: Address, spender: Address) -> Option<u64> {
+            self.allowance_expiries.get((owner, spender))
+        }
+
         /// Transfers `value` amount of tokens from the caller's account to account `to`
         ///
         /// On success a `Transfer` event is emitted
@@ -181,113 +599,450 @@ pub mod token {
             self.transfer_from_to(&from, &to, value)
         }
 
+        /// Transfers `whole_amount` whole tokens, i.e. `whole_amount * 10^decimals`
+        /// base units, from the caller's account to account `to`
+        ///
+        /// On success a `Transfer` event is emitted
+        ///
+        /// # Errors
+        ///
+        /// Returns `Overflow` error if the amount in base units doesn't fit in a
+        /// `U256`
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough tokens on
+        /// the caller's account balance
+        #[ink(message)]
+        pub fn transfer_whole(&mut self, to: Address, whole_amount: U256) -> Result<()> {
+            let value = whole_amount
+                .checked_mul(self.scaling_factor()?)
+                .ok_or(Error::Overflow)?;
+            self.transfer(to, value)
+        }
+
+        /// Transfers `value` amount of tokens from the caller's account to account `to`
+        /// with a reference attached, such as an invoice number
+        ///
+        /// On success a `Transfer` event and a `TransferWithMemo` event are emitted
+        ///
+        /// # Errors
+        ///
+        /// Returns `MemoTooLong` error if `memo` is longer than `MAX_MEMO_LEN` bytes
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough tokens on
+        /// the caller's account balance
+        #[ink(message)]
+        pub fn transfer_with_memo(
+            &mut self,
+            to: Address,
+            value: U256,
+            memo: Vec<u8>,
+        ) -> Result<()> {
+            if memo.len() > MAX_MEMO_LEN {
+                return Err(Error::MemoTooLong);
+            }
+
+            let from = self.env().caller();
+            self.transfer_from_to(&from, &to, value)?;
+            self.env().emit_event(TransferWithMemo {
+                from,
+                to,
+                value,
+                memo,
+            });
+            Ok(())
+        }
+
+        /// Transfers `value` amount of tokens from the caller's account to account `to`
+        /// and, if `to` is a contract, notifies it by calling its
+        /// `on_token_transfer(from, value, data)` message
+        ///
+        /// Callback failures are propagated: if the recipient traps or returns an
+        /// error, the whole call fails and the transfer is reverted
+        ///
+        /// On success a `Transfer` event is emitted
+        ///
+        /// # Errors
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough tokens on
+        /// the caller's account balance
+        ///
+        /// Returns `CallbackFailed` error if the recipient contract rejects the call
+        #[ink(message)]
+        pub fn transfer_and_call(&mut self, to: Address, value: U256, data: Vec<u8>) -> Result<()> {
+            let from = self.env().caller();
+            self.transfer_from_to(&from, &to, value)?;
+
+            if self.env().is_contract(&to) {
+                build_call::<ink::env::DefaultEnvironment>()
+                    .call(to)
+                    .exec_input(
+                        ExecutionInput::new(Selector::new(ON_TOKEN_TRANSFER_SELECTOR))
+                            .push_arg(from)
+                            .push_arg(value)
+                            .push_arg(data),
+                    )
+                    .returns::<()>()
+                    .try_invoke()
+                    .map_err(|_| Error::CallbackFailed)?
+                    .map_err(|_| Error::CallbackFailed)?;
+            }
+
+            Ok(())
+        }
+
         /// Allows `spender` to withdraw from the caller's account multiple times, up to
         /// the `value` amount
         ///
         /// If this function is called again it overwrites the current allowance with
-        /// `value`
+        /// `value`, and the new allowance never expires
         ///
         /// An `Approval` event is emitted
+        ///
+        /// Returns the allowance that was overwritten
+        ///
+        /// # Errors
+        ///
+        /// Returns `ApprovalTooLarge` error if `value` exceeds `max_approval`
         #[ink(message)]
-        pub fn approve(&mut self, spender: Address, value: U256) -> Result<()> {
+        pub fn approve(&mut self, spender: Address, value: U256) -> Result<U256> {
+            self.ensure_approval_allowed(value)?;
             let owner = self.env().caller();
+            let previous = self.allowance_impl(&owner, &spender);
             self.allowances.insert((&owner, &spender), &value);
+            self.allowance_expiries.remove((&owner, &spender));
             self.env().emit_event(Approval {
                 owner,
                 spender,
                 value,
             });
-            Ok(())
+            Ok(previous)
         }
 
-        /// Transfers `value` tokens on the behalf of `from` to the account `to`
+        /// Allows `spender` to withdraw from the caller's account multiple times, up to
+        /// the `value` amount, until the block timestamp passes `expiry`
         ///
-        /// This can be used to allow a contract to transfer tokens on ones behalf and/or
-        /// to charge fees in sub-currencies, for example
+        /// Afterwards the allowance counts as zero. Like `approve`, this overwrites
+        /// the current allowance
         ///
-        /// On success a `Transfer` event is emitted
+        /// An `Approval` event is emitted
         ///
         /// # Errors
         ///
-        /// Returns `InsufficientAllowance` error if there are not enough tokens allowed
-        /// for the caller to withdraw from `from`
-        ///
-        /// Returns `InsufficientBalance` error if there are not enough tokens on
-        /// the account balance of `from`
+        /// Returns `ApprovalTooLarge` error if `value` exceeds `max_approval`
         #[ink(message)]
-        pub fn transfer_from(&mut self, from: Address, to: Address, value: U256) -> Result<()> {
-            let caller = self.env().caller();
-            let allowance = self.allowance_impl(&from, &caller);
-            if allowance < value {
-                return Err(Error::InsufficientAllowance);
-            }
-            self.transfer_from_to(&from, &to, value)?;
-            // We checked that allowance >= value
-            #[allow(clippy::arithmetic_side_effects)]
-            self.allowances
-                .insert((&from, &caller), &(allowance - value));
+        pub fn approve_until(&mut self, spender: Address, value: U256, expiry: u64) -> Result<()> {
+            self.ensure_approval_allowed(value)?;
+            let owner = self.env().caller();
+            self.allowances.insert((&owner, &spender), &value);
+            self.allowance_expiries.insert((&owner, &spender), &expiry);
+            self.env().emit_event(Approval {
+                owner,
+                spender,
+                value,
+            });
             Ok(())
         }
 
-        /// Mint new tokens to the specified address
+        /// Allows `spender` to withdraw up to `value` tokens, resetting the
+        /// allowance to zero before setting it
         ///
-        /// This is a public function that can be called by anyone for faucet functionality
-        /// In a production environment, you might want to add access controls
+        /// Mitigates the known `approve` race at the call site: there's no point
+        /// where both the old and the new allowance can be spent
         ///
-        /// # Parameters
-        /// - `to`: Address to mint tokens to
-        /// - `amount`: Amount of tokens to mint
+        /// Two `Approval` events are emitted, the first for `0` and the second for
+        /// `value`
         ///
         /// # Errors
         ///
-        /// Returns `Overflow` error if minting would cause total supply overflow
+        /// Returns `ApprovalTooLarge` error if `value` exceeds `max_approval`
         #[ink(message)]
-        pub fn mint(&mut self, to: Address, amount: U256) -> Result<()> {
-            // Check for overflow in total supply
-            let new_total_supply = self
-                .total_supply
-                .checked_add(amount)
-                .ok_or(Error::Overflow)?;
-
-            // Update total supply
-            self.total_supply = new_total_supply;
-
-            // Update recipient balance
-            let to_balance = self.balance_of_impl(&to);
-            let new_balance = to_balance.checked_add(amount).ok_or(Error::Overflow)?;
-            self.balances.insert(&to, &new_balance);
-
-            // Emit events
-            self.env().emit_event(Transfer {
-                from: None,
-                to: Some(to),
-                value: amount,
-            });
-
-            self.env().emit_event(Mint { to, value: amount });
-
+        pub fn safe_approve(&mut self, spender: Address, value: U256) -> Result<()> {
+            self.ensure_approval_allowed(value)?;
+            self.approve(spender, U256::zero())?;
+            self.approve(spender, value)?;
             Ok(())
         }
 
-        /// Internal transfer function
+        /// Sets `value` as the allowance of `spender` over the tokens of `owner`,
+        /// authorized by a signature from `owner` instead of a transaction
         ///
-        /// Transfers `value` amount of tokens from `from` to `to`
+        /// The signature must be an ECDSA signature by `owner` over the hash returned
+        /// by `permit_hash` for the owner's current nonce. A successful permit consumes
+        /// the nonce, so each signature can only be used once
         ///
-        /// On success a `Transfer` event is emitted
+        /// An `Approval` event is emitted
         ///
         /// # Errors
         ///
-        /// Returns `InsufficientBalance` error if there are not enough tokens on
-        /// the `from` account balance
-        fn transfer_from_to(&mut self, from: &Address, to: &Address, value: U256) -> Result<()> {
-            let from_balance = self.balance_of_impl(from);
-            if from_balance < value {
-                return Err(Error::InsufficientBalance);
+        /// Returns `PermitExpired` error if the current block timestamp is past `deadline`
+        ///
+        /// Returns `InvalidSignature` error if the signature does not recover to `owner`
+        ///
+        /// Returns `ApprovalTooLarge` error if `value` exceeds `max_approval`
+        #[ink(message)]
+        pub fn permit(
+            &mut self,
+            owner: Address,
+            spender: Address,
+            value: U256,
+            deadline: u64,
+            signature: [u8; 65],
+        ) -> Result<()> {
+            if self.env().block_timestamp() > deadline {
+                return Err(Error::PermitExpired);
             }
+            self.ensure_approval_allowed(value)?;
 
-            // We checked that from_balance >= value
-            #[allow(clippy::arithmetic_side_effects)]
-            self.balances.insert(from, &(from_balance - value));
+            let nonce = self.nonces(owner);
+            let message_hash = self.permit_hash(owner, spender, value, nonce, deadline);
+            if self.recover_signer(&signature, &message_hash)? != owner {
+                return Err(Error::InvalidSignature);
+            }
+
+            let next_nonce = nonce.checked_add(1).ok_or(Error::Overflow)?;
+            self.nonces.insert(owner, &next_nonce);
+            self.allowances.insert((&owner, &spender), &value);
+            self.allowance_expiries.remove((&owner, &spender));
+            self.env().emit_event(Approval {
+                owner,
+                spender,
+                value,
+            });
+            Ok(())
+        }
+
+        /// Returns the nonce the next permit of `owner` must be signed with
+        #[ink(message)]
+        pub fn nonces(&self, owner: Address) -> u64 {
+            self.nonces.get(owner).unwrap_or_default()
+        }
+
+        /// Returns the hash `owner` must sign to authorize a `permit`
+        ///
+        /// The hash is the Keccak-256 of the SCALE encoded tuple
+        /// `(PERMIT_DOMAIN, token address, owner, spender, value, nonce, deadline)`,
+        /// which binds the signature to this token contract
+        #[ink(message)]
+        pub fn permit_hash(
+            &self,
+            owner: Address,
+            spender: Address,
+            value: U256,
+            nonce: u64,
+            deadline: u64,
+        ) -> [u8; 32] {
+            let payload = (
+                PERMIT_DOMAIN,
+                self.env().address(),
+                owner,
+                spender,
+                value,
+                nonce,
+                deadline,
+            );
+            self.env().hash_encoded::<Keccak256, _>(&payload)
+        }
+
+        /// Recovers the address which produced `signature` over `message_hash`
+        ///
+        /// # Errors
+        ///
+        /// Returns `InvalidSignature` error if no public key can be recovered
+        fn recover_signer(&self, signature: &[u8; 65], message_hash: &[u8; 32]) -> Result<Address> {
+            let public_key = self
+                .env()
+                .ecdsa_recover(signature, message_hash)
+                .map_err(|_| Error::InvalidSignature)?;
+            let signer = self
+                .env()
+                .ecdsa_to_eth_address(&public_key)
+                .map_err(|_| Error::InvalidSignature)?;
+            Ok(Address::from(signer))
+        }
+
+        /// Transfers `value` tokens on the behalf of `from` to the account `to`
+        ///
+        /// This can be used to allow a contract to transfer tokens on ones behalf and/or
+        /// to charge fees in sub-currencies, for example
+        ///
+        /// On success a `Transfer` event is emitted, followed by an `Approval` event
+        /// with the decremented allowance
+        ///
+        /// # Errors
+        ///
+        /// Returns `InsufficientAllowance` error if there are not enough tokens allowed
+        /// for the caller to withdraw from `from`
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough tokens on
+        /// the account balance of `from`
+        #[ink(message)]
+        pub fn transfer_from(&mut self, from: Address, to: Address, value: U256) -> Result<()> {
+            let caller = self.env().caller();
+            let allowance = self
+                .allowance_impl(&from, &caller)
+                .checked_sub(value)
+                .ok_or(Error::InsufficientAllowance)?;
+            self.transfer_from_to(&from, &to, value)?;
+            self.allowances.insert((&from, &caller), &allowance);
+            self.env().emit_event(Approval {
+                owner: from,
+                spender: caller,
+                value: allowance,
+            });
+            Ok(())
+        }
+
+        /// Mint new tokens to the specified address
+        ///
+        /// This is a public function that can be called by anyone for faucet functionality
+        /// In a production environment, you might want to add access controls
+        ///
+        /// Minting is frozen for good once ownership was renounced
+        ///
+        /// # Parameters
+        /// - `to`: Address to mint tokens to
+        /// - `amount`: Amount of tokens to mint
+        ///
+        /// # Errors
+        ///
+        /// Returns `NotAuthorized` error if ownership was renounced
+        ///
+        /// Returns `Overflow` error if minting would cause total supply overflow
+        #[ink(message)]
+        pub fn mint(&mut self, to: Address, amount: U256) -> Result<()> {
+            if self.owner.is_none() {
+                return Err(Error::NotAuthorized);
+            }
+
+            // Check for overflow in total supply
+            let new_total_supply = self
+                .total_supply
+                .checked_add(amount)
+                .ok_or(Error::Overflow)?;
+
+            // Update total supply and the minted total
+            self.total_supply = new_total_supply;
+            self.total_minted = self.total_minted.saturating_add(amount);
+
+            // Update recipient balance
+            self.update_balance_snapshot(&to);
+            let to_balance = self.balance_of_impl(&to);
+            let new_balance = to_balance.checked_add(amount).ok_or(Error::Overflow)?;
+            self.balances.insert(&to, &new_balance);
+
+            // Emit events
+            self.env().emit_event(Transfer {
+                from: None,
+                to: Some(to),
+                value: amount,
+            });
+
+            self.env().emit_event(Mint { to, value: amount });
+
+            Ok(())
+        }
+
+        /// Destroys `amount` of the caller's tokens, reducing the total supply
+        ///
+        /// A `Transfer` event to `None` is emitted
+        ///
+        /// # Errors
+        ///
+        /// Returns `InsufficientBalance` error if the caller holds less than `amount`
+        #[ink(message)]
+        pub fn burn(&mut self, amount: U256) -> Result<()> {
+            let from = self.env().caller();
+            let from_balance = self
+                .balance_of_impl(&from)
+                .checked_sub(amount)
+                .ok_or(Error::InsufficientBalance)?;
+
+            self.update_balance_snapshot(&from);
+            self.balances.insert(&from, &from_balance);
+            // Cannot underflow since every balance is bounded by the total supply
+            self.total_supply = self.total_supply.saturating_sub(amount);
+            self.total_burned = self.total_burned.saturating_add(amount);
+
+            self.env().emit_event(Transfer {
+                from: Some(from),
+                to: None,
+                value: amount,
+            });
+            Ok(())
+        }
+
+        /// Mints tokens to many accounts at once (owner only)
+        ///
+        /// The total supply is checked for overflow before any balance changes, so
+        /// either every entry is minted or none is. A `Transfer` and a `Mint` event
+        /// are emitted per entry
+        ///
+        /// # Parameters
+        /// - `entries`: `(recipient, amount)` pairs to mint
+        ///
+        /// # Errors
+        ///
+        /// Returns `NotAuthorized` error if the caller is not the token owner
+        ///
+        /// Returns `Overflow` error if minting the sum of all amounts would cause
+        /// total supply overflow
+        #[ink(message)]
+        pub fn mint_batch(&mut self, entries: Vec<(Address, U256)>) -> Result<()> {
+            self.ensure_owner()?;
+
+            // Check for overflow of the whole batch up front
+            let new_total_supply = entries
+                .iter()
+                .try_fold(self.total_supply, |total, (_, amount)| {
+                    total.checked_add(*amount)
+                })
+                .ok_or(Error::Overflow)?;
+
+            // Update total supply and the minted total
+            let minted = new_total_supply.saturating_sub(self.total_supply);
+            self.total_supply = new_total_supply;
+            self.total_minted = self.total_minted.saturating_add(minted);
+
+            for (to, amount) in entries {
+                // Cannot overflow since every balance is bounded by the total supply
+                self.update_balance_snapshot(&to);
+                let to_balance = self.balance_of_impl(&to);
+                let new_balance = to_balance.checked_add(amount).ok_or(Error::Overflow)?;
+                self.balances.insert(&to, &new_balance);
+
+                // Emit events
+                self.env().emit_event(Transfer {
+                    from: None,
+                    to: Some(to),
+                    value: amount,
+                });
+
+                self.env().emit_event(Mint { to, value: amount });
+            }
+
+            Ok(())
+        }
+
+        /// Internal transfer function
+        ///
+        /// Transfers `value` amount of tokens from `from` to `to`
+        ///
+        /// On success a `Transfer` event is emitted
+        ///
+        /// # Errors
+        ///
+        /// Returns `InsufficientBalance` error if there are not enough tokens on
+        /// the `from` account balance
+        fn transfer_from_to(&mut self, from: &Address, to: &Address, value: U256) -> Result<()> {
+            let from_balance = self
+                .balance_of_impl(from)
+                .checked_sub(value)
+                .ok_or(Error::InsufficientBalance)?;
+
+            self.update_balance_snapshot(from);
+            self.update_balance_snapshot(to);
+
+            self.balances.insert(from, &from_balance);
 
             let to_balance = self.balance_of_impl(to);
             let new_to_balance = to_balance.checked_add(value).ok_or(Error::Overflow)?;
@@ -301,6 +1056,107 @@ pub mod token {
             Ok(())
         }
 
+        /// Returns the token owner, `None` once ownership was renounced
+        #[ink(message)]
+        pub fn owner(&self) -> Option<Address> {
+            self.owner
+        }
+
+        /// Gives up ownership for good, disabling every owner-only operation and
+        /// freezing minting
+        ///
+        /// An `OwnershipRenounced` event is emitted
+        ///
+        /// # Errors
+        ///
+        /// Returns `NotAuthorized` error if the caller is not the token owner
+        #[ink(message)]
+        pub fn renounce_ownership(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            let previous_owner = self.env().caller();
+            self.owner = None;
+            self.pending_owner = None;
+            self.env().emit_event(OwnershipRenounced { previous_owner });
+            Ok(())
+        }
+
+        /// Returns the account ownership was offered to, if any
+        #[ink(message)]
+        pub fn pending_owner(&self) -> Option<Address> {
+            self.pending_owner
+        }
+
+        /// Offers ownership to `new_owner`, who becomes owner once it calls
+        /// `accept_ownership`
+        ///
+        /// Replaces any previous offer. An `OwnershipTransferStarted` event is
+        /// emitted
+        ///
+        /// # Errors
+        ///
+        /// Returns `NotAuthorized` error if the caller is not the token owner
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: Address) -> Result<()> {
+            self.ensure_owner()?;
+            self.pending_owner = Some(new_owner);
+            self.env().emit_event(OwnershipTransferStarted {
+                previous_owner: self.env().caller(),
+                new_owner,
+            });
+            Ok(())
+        }
+
+        /// Accepts the ownership offered to the caller
+        ///
+        /// An `OwnershipTransferred` event is emitted
+        ///
+        /// # Errors
+        ///
+        /// Returns `NotAuthorized` error if the caller is not the pending owner
+        #[ink(message)]
+        pub fn accept_ownership(&mut self) -> Result<()> {
+            let new_owner = self.env().caller();
+            if self.pending_owner != Some(new_owner) {
+                return Err(Error::NotAuthorized);
+            }
+
+            let previous_owner = self.owner.replace(new_owner);
+            self.pending_owner = None;
+            self.env().emit_event(OwnershipTransferred {
+                previous_owner,
+                new_owner,
+            });
+            Ok(())
+        }
+
+        /// Withdraws a pending ownership offer
+        ///
+        /// # Errors
+        ///
+        /// Returns `NotAuthorized` error if the caller is not the token owner
+        #[ink(message)]
+        pub fn cancel_ownership_transfer(&mut self) -> Result<()> {
+            self.ensure_owner()?;
+            self.pending_owner = None;
+            Ok(())
+        }
+
+        /// Returns `NotAuthorized` error unless the caller is the token owner
+        fn ensure_owner(&self) -> Result<()> {
+            if self.owner != Some(self.env().caller()) {
+                return Err(Error::NotAuthorized);
+            }
+            Ok(())
+        }
+
+        /// Returns `ApprovalTooLarge` error if `value` exceeds `max_approval`
+        fn ensure_approval_allowed(&self, value: U256) -> Result<()> {
+            if self.max_approval.is_some_and(|max| value > max) {
+                return Err(Error::ApprovalTooLarge);
+            }
+            Ok(())
+        }
+
         #[ink(message)]
         pub fn address(&self) -> Address {
             self.env().address()
@@ -315,6 +1171,35 @@ pub mod token {
             ink::env::test::set_caller(sender);
         }
 
+        fn set_block_timestamp(timestamp: u64) {
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(timestamp);
+        }
+
+        /// Returns the address controlled by the secp256k1 `secret` key
+        fn signer_address(secret: [u8; 32]) -> Address {
+            let secp = secp256k1::Secp256k1::new();
+            let secret_key = secp256k1::SecretKey::from_byte_array(&secret).unwrap();
+            let public_key = secp256k1::PublicKey::from_secret_key(&secp, &secret_key);
+            let mut address = [0u8; 20];
+            ink::env::ecdsa_to_eth_address(&public_key.serialize(), &mut address).unwrap();
+            Address::from(address)
+        }
+
+        /// Signs `message_hash` with the secp256k1 `secret` key in the 65 byte
+        /// recoverable format expected by `permit`
+        fn sign(secret: [u8; 32], message_hash: [u8; 32]) -> [u8; 65] {
+            let secp = secp256k1::Secp256k1::new();
+            let secret_key = secp256k1::SecretKey::from_byte_array(&secret).unwrap();
+            let message = secp256k1::Message::from_digest(message_hash);
+            let (recovery_id, compact) = secp
+                .sign_ecdsa_recoverable(&message, &secret_key)
+                .serialize_compact();
+            let mut signature = [0u8; 65];
+            signature[..64].copy_from_slice(&compact);
+            signature[64] = i32::from(recovery_id) as u8;
+            signature
+        }
+
         #[ink::test]
         fn new_works() {
             let name = String::from("InkFundMe Token");
@@ -323,13 +1208,39 @@ pub mod token {
             let initial_supply = U256::from(1000000);
 
             set_caller(Address::from([0x01; 20]));
-            let token = Token::new(name.clone(), symbol.clone(), decimals, initial_supply);
+            let token = Token::new(name.clone(), symbol.clone(), decimals, initial_supply, None);
 
             assert_eq!(token.name(), name);
             assert_eq!(token.symbol(), symbol);
             assert_eq!(token.decimals(), decimals);
             assert_eq!(token.total_supply(), initial_supply);
             assert_eq!(token.balance_of(Address::from([0x01; 20])), initial_supply);
+            assert_eq!(token.get_version(), CONTRACT_VERSION);
+            assert_eq!(token.schema_version, CONTRACT_VERSION);
+        }
+
+        #[ink::test]
+        fn new_truncates_metadata() {
+            let token = Token::new(
+                "N".repeat(MAX_NAME_LEN + 10),
+                "S".repeat(MAX_SYMBOL_LEN + 1),
+                18,
+                U256::from(1000),
+                None,
+            );
+            assert_eq!(token.name(), "N".repeat(MAX_NAME_LEN));
+            assert_eq!(token.symbol(), "S".repeat(MAX_SYMBOL_LEN));
+
+            // Multi-byte characters are never split
+            let token = Token::new(
+                "€".repeat(MAX_NAME_LEN),
+                "€".repeat(MAX_SYMBOL_LEN),
+                18,
+                U256::from(1000),
+                None,
+            );
+            assert_eq!(token.name(), "€".repeat(MAX_NAME_LEN / 3));
+            assert_eq!(token.symbol(), "€".repeat(MAX_SYMBOL_LEN / 3));
         }
 
         #[ink::test]
@@ -339,6 +1250,7 @@ pub mod token {
                 String::from("TEST"),
                 18,
                 U256::from(1000),
+                None,
             );
 
             let recipient = Address::from([0x02; 20]);
@@ -351,6 +1263,41 @@ pub mod token {
             assert_eq!(token.total_supply(), U256::from(1500));
         }
 
+        #[ink::test]
+        fn total_minted_and_burned_track_supply() {
+            let owner = Address::from([0x01; 20]);
+            let recipient = Address::from([0x02; 20]);
+            set_caller(owner);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            assert_eq!(token.total_minted(), U256::from(1000));
+            assert_eq!(token.total_burned(), U256::zero());
+
+            assert!(token.mint(recipient, U256::from(500)).is_ok());
+            assert!(token
+                .mint_batch(vec![(owner, U256::from(100)), (recipient, U256::from(200))])
+                .is_ok());
+            assert!(token.burn(U256::from(300)).is_ok());
+            set_caller(recipient);
+            assert!(token.burn(U256::from(700)).is_ok());
+
+            assert_eq!(token.total_minted(), U256::from(1800));
+            assert_eq!(token.total_burned(), U256::from(1000));
+            assert_eq!(token.total_supply(), U256::from(800));
+            assert_eq!(token.balance_of(owner), U256::from(800));
+            assert_eq!(token.balance_of(recipient), U256::zero());
+
+            // Failed burns change nothing
+            assert_eq!(token.burn(U256::from(1)), Err(Error::InsufficientBalance));
+            assert_eq!(token.total_burned(), U256::from(1000));
+            assert_eq!(token.total_supply(), U256::from(800));
+        }
+
         #[ink::test]
         fn transfer_works() {
             set_caller(Address::from([0x01; 20]));
@@ -359,6 +1306,7 @@ pub mod token {
                 String::from("TEST"),
                 18,
                 U256::from(1000),
+                None,
             );
 
             let recipient = Address::from([0x02; 20]);
@@ -379,6 +1327,7 @@ pub mod token {
                 String::from("TEST"),
                 18,
                 U256::from(100),
+                None,
             );
 
             let recipient = Address::from([0x02; 20]);
@@ -388,6 +1337,44 @@ pub mod token {
             assert_eq!(result, Err(Error::InsufficientBalance));
         }
 
+        #[ink::test]
+        fn transfer_entire_balance_and_allowance_works() {
+            let owner = Address::from([0x01; 20]);
+            let spender = Address::from([0x02; 20]);
+            let recipient = Address::from([0x03; 20]);
+
+            set_caller(owner);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+
+            // Transferring the whole balance leaves exactly zero
+            assert!(token.transfer(spender, U256::from(1000)).is_ok());
+            assert_eq!(token.balance_of(owner), U256::zero());
+            assert_eq!(token.balance_of(spender), U256::from(1000));
+
+            // Spending the whole allowance leaves exactly zero
+            set_caller(spender);
+            assert!(token.approve(owner, U256::from(1000)).is_ok());
+            set_caller(owner);
+            assert!(token
+                .transfer_from(spender, recipient, U256::from(1000))
+                .is_ok());
+            assert_eq!(token.allowance(spender, owner), U256::zero());
+            assert_eq!(token.balance_of(spender), U256::zero());
+            assert_eq!(token.balance_of(recipient), U256::from(1000));
+
+            // Nothing left to move
+            assert_eq!(
+                token.transfer_from(spender, recipient, U256::from(1)),
+                Err(Error::InsufficientAllowance)
+            );
+        }
+
         #[ink::test]
         fn approve_and_transfer_from_works() {
             let owner = Address::from([0x01; 20]);
@@ -400,12 +1387,13 @@ pub mod token {
                 String::from("TEST"),
                 18,
                 U256::from(1000),
+                None,
             );
 
             // Owner approves spender
             let approve_amount = U256::from(200);
-            let result = token.approve(spender, approve_amount);
-            assert!(result.is_ok());
+            let previous = token.approve(spender, approve_amount);
+            assert_eq!(previous, Ok(U256::zero()));
             assert_eq!(token.allowance(owner, spender), approve_amount);
 
             // Spender transfers from owner to recipient
@@ -417,6 +1405,1027 @@ pub mod token {
             assert_eq!(token.balance_of(owner), U256::from(900));
             assert_eq!(token.balance_of(recipient), transfer_amount);
             assert_eq!(token.allowance(owner, spender), U256::from(100));
+
+            // Re-approving returns the remaining allowance it overwrites
+            set_caller(owner);
+            let previous = token.approve(spender, U256::from(500));
+            assert_eq!(previous, Ok(U256::from(100)));
+            assert_eq!(token.allowance(owner, spender), U256::from(500));
+        }
+
+        #[ink::test]
+        fn transfer_from_emits_decremented_approval() {
+            let owner = Address::from([0x01; 20]);
+            let spender = Address::from([0x02; 20]);
+            let recipient = Address::from([0x03; 20]);
+
+            set_caller(owner);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            assert!(token.approve(spender, U256::from(200)).is_ok());
+
+            set_caller(spender);
+            let last_approval = || {
+                let event = ink::env::test::recorded_events().last().unwrap();
+                <Approval as ink::scale::Decode>::decode(&mut &event.data[..]).unwrap()
+            };
+
+            assert!(token
+                .transfer_from(owner, recipient, U256::from(150))
+                .is_ok());
+            let event = last_approval();
+            assert_eq!(event.owner, owner);
+            assert_eq!(event.spender, spender);
+            assert_eq!(event.value, U256::from(50));
+
+            // Exhausting the allowance reports exactly zero
+            assert!(token
+                .transfer_from(owner, recipient, U256::from(50))
+                .is_ok());
+            assert_eq!(last_approval().value, U256::zero());
+            assert_eq!(token.allowance(owner, spender), U256::zero());
+
+            // Initial Transfer + Approval + 2 * (Transfer + Approval)
+            assert_eq!(ink::env::test::recorded_events().count(), 6);
+        }
+
+        #[ink::test]
+        fn safe_approve_resets_allowance_first() {
+            let owner = Address::from([0x01; 20]);
+            let spender = Address::from([0x02; 20]);
+
+            set_caller(owner);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            assert!(token.approve(spender, U256::from(100)).is_ok());
+
+            assert_eq!(token.safe_approve(spender, U256::from(300)), Ok(()));
+            assert_eq!(token.allowance(owner, spender), U256::from(300));
+
+            let emitted_events: Vec<_> = ink::env::test::recorded_events().into_iter().collect();
+            assert_eq!(emitted_events.len(), 4); // Initial Transfer + Approval + 2 * Approval
+            let values: Vec<_> = emitted_events[2..]
+                .iter()
+                .map(|event| {
+                    <Approval as ink::scale::Decode>::decode(&mut &event.data[..])
+                        .unwrap()
+                        .value
+                })
+                .collect();
+            assert_eq!(values, vec![U256::zero(), U256::from(300)]);
+        }
+
+        #[ink::test]
+        fn approve_respects_max_approval() {
+            let owner = Address::from([0x01; 20]);
+            let spender = Address::from([0x02; 20]);
+
+            set_caller(owner);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                Some(U256::from(500)),
+            );
+            assert_eq!(token.max_approval(), Some(U256::from(500)));
+
+            // Under and at the cap
+            assert!(token.approve(spender, U256::from(499)).is_ok());
+            assert!(token.approve(spender, U256::from(500)).is_ok());
+            assert_eq!(token.allowance(owner, spender), U256::from(500));
+
+            // Over the cap leaves the allowance untouched
+            assert_eq!(
+                token.approve(spender, U256::from(501)),
+                Err(Error::ApprovalTooLarge)
+            );
+            assert_eq!(
+                token.approve(spender, U256::MAX),
+                Err(Error::ApprovalTooLarge)
+            );
+            assert_eq!(token.allowance(owner, spender), U256::from(500));
+        }
+
+        #[ink::test]
+        fn permit_respects_max_approval() {
+            let secret = [0x11; 32];
+            let owner = signer_address(secret);
+            let spender = Address::from([0x02; 20]);
+            let value = U256::from(501);
+            let deadline = 1000;
+
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                Some(U256::from(500)),
+            );
+            set_block_timestamp(500);
+
+            let signature = sign(
+                secret,
+                token.permit_hash(owner, spender, value, 0, deadline),
+            );
+
+            set_caller(spender);
+            let result = token.permit(owner, spender, value, deadline, signature);
+            assert_eq!(result, Err(Error::ApprovalTooLarge));
+            assert_eq!(token.allowance(owner, spender), U256::zero());
+            // The signature wasn't consumed
+            assert_eq!(token.nonces(owner), 0);
+        }
+
+        #[ink::test]
+        fn permit_sets_allowance() {
+            let secret = [0x11; 32];
+            let owner = signer_address(secret);
+            let spender = Address::from([0x02; 20]);
+            let value = U256::from(300);
+            let deadline = 1000;
+
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            set_block_timestamp(500);
+
+            let signature = sign(
+                secret,
+                token.permit_hash(owner, spender, value, 0, deadline),
+            );
+
+            // Anyone may submit the signed permit
+            set_caller(spender);
+            let result = token.permit(owner, spender, value, deadline, signature);
+            assert!(result.is_ok());
+
+            assert_eq!(token.allowance(owner, spender), value);
+            assert_eq!(token.nonces(owner), 1);
+        }
+
+        #[ink::test]
+        fn permit_replay_fails() {
+            let secret = [0x11; 32];
+            let owner = signer_address(secret);
+            let spender = Address::from([0x02; 20]);
+            let value = U256::from(300);
+            let deadline = 1000;
+
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            set_block_timestamp(500);
+
+            let signature = sign(
+                secret,
+                token.permit_hash(owner, spender, value, 0, deadline),
+            );
+            assert!(token
+                .permit(owner, spender, value, deadline, signature)
+                .is_ok());
+
+            // The nonce was consumed, so the same signature no longer matches
+            let result = token.permit(owner, spender, value, deadline, signature);
+            assert_eq!(result, Err(Error::InvalidSignature));
+            assert_eq!(token.nonces(owner), 1);
+        }
+
+        #[ink::test]
+        fn permit_expired_fails() {
+            let secret = [0x11; 32];
+            let owner = signer_address(secret);
+            let spender = Address::from([0x02; 20]);
+            let value = U256::from(300);
+            let deadline = 1000;
+
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            set_block_timestamp(deadline + 1);
+
+            let signature = sign(
+                secret,
+                token.permit_hash(owner, spender, value, 0, deadline),
+            );
+            let result = token.permit(owner, spender, value, deadline, signature);
+            assert_eq!(result, Err(Error::PermitExpired));
+            assert_eq!(token.allowance(owner, spender), U256::zero());
+        }
+
+        #[ink::test]
+        fn permit_wrong_signer_fails() {
+            let owner = signer_address([0x11; 32]);
+            let spender = Address::from([0x02; 20]);
+            let value = U256::from(300);
+            let deadline = 1000;
+
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            set_block_timestamp(500);
+
+            // Signed by a different key than the one controlling `owner`
+            let signature = sign(
+                [0x22; 32],
+                token.permit_hash(owner, spender, value, 0, deadline),
+            );
+            let result = token.permit(owner, spender, value, deadline, signature);
+            assert_eq!(result, Err(Error::InvalidSignature));
+            assert_eq!(token.nonces(owner), 0);
+        }
+
+        #[ink::test]
+        fn snapshot_records_historical_balances() {
+            let owner = Address::from([0x01; 20]);
+            let recipient = Address::from([0x02; 20]);
+
+            set_caller(owner);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+
+            let snapshot_id = token.snapshot().unwrap();
+            assert_eq!(snapshot_id, 1);
+            assert_eq!(token.current_snapshot_id(), 1);
+
+            assert!(token.transfer(recipient, U256::from(300)).is_ok());
+            assert!(token.mint(recipient, U256::from(50)).is_ok());
+
+            // Historical balances are frozen at the snapshot
+            assert_eq!(token.balance_of_at(owner, snapshot_id), U256::from(1000));
+            assert_eq!(token.balance_of_at(recipient, snapshot_id), U256::zero());
+
+            // Current balances reflect the transfer and mint
+            assert_eq!(token.balance_of(owner), U256::from(700));
+            assert_eq!(token.balance_of(recipient), U256::from(350));
+
+            // A later snapshot sees the updated balances
+            let second_id = token.snapshot().unwrap();
+            assert!(token.transfer(recipient, U256::from(100)).is_ok());
+            assert_eq!(token.balance_of_at(owner, snapshot_id), U256::from(1000));
+            assert_eq!(token.balance_of_at(owner, second_id), U256::from(700));
+            assert_eq!(token.balance_of_at(recipient, second_id), U256::from(350));
+            assert_eq!(token.balance_of(recipient), U256::from(450));
+        }
+
+        #[ink::test]
+        fn balance_of_at_unknown_snapshot_is_zero() {
+            let owner = Address::from([0x01; 20]);
+
+            set_caller(owner);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+
+            assert_eq!(token.balance_of_at(owner, 0), U256::zero());
+            assert_eq!(token.balance_of_at(owner, 1), U256::zero());
+
+            let snapshot_id = token.snapshot().unwrap();
+            assert_eq!(token.balance_of_at(owner, snapshot_id), U256::from(1000));
+            assert_eq!(token.balance_of_at(owner, snapshot_id + 1), U256::zero());
+        }
+
+        #[ink::test]
+        fn balance_of_at_searches_long_history() {
+            let owner = Address::from([0x01; 20]);
+            let recipient = Address::from([0x02; 20]);
+
+            set_caller(owner);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+
+            // Snapshots 1 to 9, each followed by a transfer of 10
+            for _ in 0..9 {
+                token.snapshot().unwrap();
+                assert!(token.transfer(recipient, U256::from(10)).is_ok());
+            }
+            // Snapshots 10 and 11 without any transfer in between
+            token.snapshot().unwrap();
+            token.snapshot().unwrap();
+            assert!(token.transfer(recipient, U256::from(10)).is_ok());
+
+            for id in 1..=9u32 {
+                assert_eq!(
+                    token.balance_of_at(owner, id),
+                    U256::from(1000 - 10 * (id - 1))
+                );
+            }
+            assert_eq!(token.balance_of_at(owner, 10), U256::from(910));
+            assert_eq!(token.balance_of_at(owner, 11), U256::from(910));
+            assert_eq!(token.balance_of(owner), U256::from(900));
+        }
+
+        #[ink::test]
+        fn snapshot_non_owner_fails() {
+            set_caller(Address::from([0x01; 20]));
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+
+            set_caller(Address::from([0x02; 20]));
+            assert_eq!(token.snapshot(), Err(Error::NotAuthorized));
+            assert_eq!(token.current_snapshot_id(), 0);
+        }
+
+        #[ink::test]
+        fn transfer_and_call_to_account_works() {
+            set_caller(Address::from([0x01; 20]));
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+
+            // Plain accounts receive the tokens without a callback
+            let recipient = Address::from([0x02; 20]);
+            let result = token.transfer_and_call(recipient, U256::from(100), vec![1, 2, 3]);
+            assert!(result.is_ok());
+
+            assert_eq!(token.balance_of(Address::from([0x01; 20])), U256::from(900));
+            assert_eq!(token.balance_of(recipient), U256::from(100));
+        }
+
+        #[ink::test]
+        #[should_panic(expected = "failed getting code hash")]
+        fn transfer_and_call_to_contract_invokes_callback() {
+            set_caller(Address::from([0x01; 20]));
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+
+            // Contract recipients are called back, which panics off-chain because
+            // no receiver code is deployed at the address. The callback itself is
+            // covered end to end by inkfundme's `on_token_transfer` E2E test
+            let receiver = Address::from([0x03; 20]);
+            ink::env::test::set_contract(receiver);
+            let _result = token.transfer_and_call(receiver, U256::from(100), vec![1, 2, 3]);
+        }
+
+        #[ink::test]
+        fn set_name_and_symbol_works() {
+            set_caller(Address::from([0x01; 20]));
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+
+            assert_eq!(token.set_name(String::from("Renamed Token")), Ok(()));
+            assert_eq!(token.set_symbol(String::from("RNM")), Ok(()));
+            assert_eq!(token.name(), "Renamed Token");
+            assert_eq!(token.symbol(), "RNM");
+            assert_eq!(token.decimals(), 18);
+
+            let emitted_events = ink::env::test::recorded_events();
+            assert_eq!(emitted_events.into_iter().count(), 3); // Transfer + 2 MetadataUpdated
+        }
+
+        #[ink::test]
+        fn scaling_factor_works() {
+            let token = |decimals| {
+                Token::new(
+                    String::from("Test Token"),
+                    String::from("TEST"),
+                    decimals,
+                    U256::from(1000),
+                    None,
+                )
+            };
+
+            assert_eq!(token(0).scaling_factor(), Ok(U256::one()));
+            assert_eq!(
+                token(18).scaling_factor(),
+                Ok(U256::from(1_000_000_000_000_000_000u128))
+            );
+            // Decimals are clamped well below the largest power of ten in a U256
+            assert_eq!(
+                token(u8::MAX).scaling_factor(),
+                Ok(U256::from(10).pow(U256::from(MAX_DECIMALS)))
+            );
+        }
+
+        #[ink::test]
+        fn new_clamps_decimals() {
+            let token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                u8::MAX,
+                U256::from(1000),
+                None,
+            );
+            assert_eq!(token.decimals(), MAX_DECIMALS);
+
+            let token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                MAX_DECIMALS,
+                U256::from(1000),
+                None,
+            );
+            assert_eq!(token.decimals(), MAX_DECIMALS);
+        }
+
+        #[ink::test]
+        fn decimals_are_immutable() {
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            let scaling_factor = token.scaling_factor();
+
+            // No owner operation touches the decimals
+            assert!(token.mint(owner, U256::from(100)).is_ok());
+            assert!(token
+                .transfer(Address::from([0x02; 20]), U256::from(100))
+                .is_ok());
+            assert!(token.set_name(String::from("Renamed Token")).is_ok());
+            assert!(token.set_symbol(String::from("RNM")).is_ok());
+            assert!(token.snapshot().is_ok());
+            assert!(token.renounce_ownership().is_ok());
+
+            assert_eq!(token.decimals(), 18);
+            assert_eq!(token.scaling_factor(), scaling_factor);
+        }
+
+        #[ink::test]
+        fn set_name_and_symbol_reject_invalid_lengths() {
+            set_caller(Address::from([0x01; 20]));
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+
+            assert_eq!(token.set_name(String::new()), Err(Error::InvalidMetadata));
+            assert_eq!(
+                token.set_name("a".repeat(MAX_NAME_LEN + 1)),
+                Err(Error::InvalidMetadata)
+            );
+            assert_eq!(
+                token.set_symbol("A".repeat(MAX_SYMBOL_LEN + 1)),
+                Err(Error::InvalidMetadata)
+            );
+            assert_eq!(token.set_name("a".repeat(MAX_NAME_LEN)), Ok(()));
+            assert_eq!(token.symbol(), "TEST");
+        }
+
+        #[ink::test]
+        fn set_name_and_symbol_non_owner_fails() {
+            set_caller(Address::from([0x01; 20]));
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+
+            set_caller(Address::from([0x02; 20]));
+            assert_eq!(
+                token.set_name(String::from("Hijacked")),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(
+                token.set_symbol(String::from("HJK")),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(token.name(), "Test Token");
+            assert_eq!(token.symbol(), "TEST");
+        }
+
+        #[ink::test]
+        fn allowances_of_works() {
+            let first = Address::from([0x01; 20]);
+            let second = Address::from([0x02; 20]);
+            let spender = Address::from([0x03; 20]);
+
+            set_caller(first);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            assert!(token.approve(spender, U256::from(100)).is_ok());
+            set_caller(second);
+            assert!(token.approve(spender, U256::from(200)).is_ok());
+
+            assert_eq!(
+                token.allowances_of(vec![(first, spender), (second, spender), (spender, first)]),
+                Ok(vec![U256::from(100), U256::from(200), U256::zero()])
+            );
+            assert_eq!(
+                token
+                    .allowances_of(vec![(first, spender); MAX_BATCH_LEN])
+                    .map(|allowances| allowances.len()),
+                Ok(MAX_BATCH_LEN)
+            );
+            assert_eq!(
+                token.allowances_of(vec![(first, spender); MAX_BATCH_LEN + 1]),
+                Err(Error::BatchTooLarge)
+            );
+        }
+
+        #[ink::test]
+        fn balances_of_works() {
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::zero(),
+                None,
+            );
+            let first = Address::from([0x02; 20]);
+            let second = Address::from([0x03; 20]);
+            let unknown = Address::from([0x04; 20]);
+            assert!(token.mint(first, U256::from(100)).is_ok());
+            assert!(token.mint(second, U256::from(200)).is_ok());
+
+            assert_eq!(
+                token.balances_of(vec![second, unknown, first]),
+                Ok(vec![U256::from(200), U256::zero(), U256::from(100)])
+            );
+            assert_eq!(
+                token
+                    .balances_of(vec![first; MAX_BATCH_LEN])
+                    .map(|balances| balances.len()),
+                Ok(MAX_BATCH_LEN)
+            );
+            assert_eq!(
+                token.balances_of(vec![first; MAX_BATCH_LEN + 1]),
+                Err(Error::BatchTooLarge)
+            );
+        }
+
+        #[test]
+        fn error_codes_are_stable() {
+            let codes = [
+                (Error::InsufficientBalance, 1),
+                (Error::InsufficientAllowance, 2),
+                (Error::Overflow, 3),
+                (Error::InvalidSignature, 4),
+                (Error::PermitExpired, 5),
+                (Error::NotAuthorized, 6),
+                (Error::CallbackFailed, 7),
+                (Error::InvalidMetadata, 8),
+                (Error::MemoTooLong, 9),
+                (Error::ApprovalTooLarge, 10),
+                (Error::BatchTooLarge, 11),
+            ];
+            for (error, code) in codes {
+                assert_eq!(error.code(), code, "{:?}", error);
+            }
+        }
+
+        #[ink::test]
+        fn transfer_with_memo_works() {
+            let sender = Address::from([0x01; 20]);
+            let recipient = Address::from([0x02; 20]);
+            set_caller(sender);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+
+            let memo = b"INV-2024-001".to_vec();
+            let result = token.transfer_with_memo(recipient, U256::from(100), memo.clone());
+            assert!(result.is_ok());
+            assert_eq!(token.balance_of(sender), U256::from(900));
+            assert_eq!(token.balance_of(recipient), U256::from(100));
+
+            let emitted_events: Vec<_> = ink::env::test::recorded_events().into_iter().collect();
+            assert_eq!(emitted_events.len(), 3); // Initial Transfer + Transfer + TransferWithMemo
+            let event =
+                <TransferWithMemo as ink::scale::Decode>::decode(&mut &emitted_events[2].data[..])
+                    .unwrap();
+            assert_eq!(event.from, sender);
+            assert_eq!(event.to, recipient);
+            assert_eq!(event.value, U256::from(100));
+            assert_eq!(event.memo, memo);
+        }
+
+        #[ink::test]
+        fn transfer_with_memo_too_long_fails() {
+            let sender = Address::from([0x01; 20]);
+            let recipient = Address::from([0x02; 20]);
+            set_caller(sender);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+
+            let result =
+                token.transfer_with_memo(recipient, U256::from(100), vec![0; MAX_MEMO_LEN + 1]);
+            assert_eq!(result, Err(Error::MemoTooLong));
+            assert_eq!(token.balance_of(sender), U256::from(1000));
+            assert_eq!(token.balance_of(recipient), U256::zero());
+        }
+
+        #[ink::test]
+        fn mint_batch_works() {
+            set_caller(Address::from([0x01; 20]));
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            let recipients = [
+                Address::from([0x02; 20]),
+                Address::from([0x03; 20]),
+                Address::from([0x04; 20]),
+            ];
+
+            let result = token.mint_batch(vec![
+                (recipients[0], U256::from(100)),
+                (recipients[1], U256::from(200)),
+                (recipients[2], U256::from(300)),
+            ]);
+            assert!(result.is_ok());
+
+            assert_eq!(
+                token.balances_of(recipients.to_vec()),
+                Ok(vec![U256::from(100), U256::from(200), U256::from(300)])
+            );
+            assert_eq!(token.total_supply(), U256::from(1600));
+
+            let emitted_events = ink::env::test::recorded_events();
+            assert_eq!(emitted_events.into_iter().count(), 7); // Initial Transfer + 3 * (Transfer + Mint)
+        }
+
+        #[ink::test]
+        fn mint_batch_overflow_mints_nothing() {
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::MAX - U256::from(10),
+                None,
+            );
+            let recipient = Address::from([0x02; 20]);
+
+            // Each amount fits on its own, but not their sum
+            let result =
+                token.mint_batch(vec![(recipient, U256::from(5)), (recipient, U256::from(6))]);
+            assert_eq!(result, Err(Error::Overflow));
+            assert_eq!(token.balance_of(recipient), U256::zero());
+            assert_eq!(token.total_supply(), U256::MAX - U256::from(10));
+        }
+
+        #[ink::test]
+        fn mint_batch_non_owner_fails() {
+            set_caller(Address::from([0x01; 20]));
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+
+            let recipient = Address::from([0x02; 20]);
+            set_caller(recipient);
+            assert_eq!(
+                token.mint_batch(vec![(recipient, U256::from(100))]),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(token.balance_of(recipient), U256::zero());
+        }
+
+        /// Seeds a balance directly, bypassing the total supply bookkeeping
+        fn set_balance(token: &mut Token, account: Address, balance: U256) {
+            token.balances.insert(account, &balance);
+        }
+
+        #[ink::test]
+        fn mint_total_supply_overflow_fails() {
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::MAX - U256::one(),
+                None,
+            );
+            let recipient = Address::from([0x02; 20]);
+
+            // Minting exactly up to the maximum still works
+            assert!(token.mint(recipient, U256::one()).is_ok());
+            assert_eq!(token.total_supply(), U256::MAX);
+
+            assert_eq!(token.mint(recipient, U256::one()), Err(Error::Overflow));
+            assert_eq!(token.total_supply(), U256::MAX);
+            assert_eq!(token.balance_of(recipient), U256::one());
+        }
+
+        #[ink::test]
+        fn mint_balance_overflow_fails() {
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::zero(),
+                None,
+            );
+            let recipient = Address::from([0x02; 20]);
+            set_balance(&mut token, recipient, U256::MAX);
+
+            // Total supply has room, the recipient's balance doesn't
+            assert_eq!(token.mint(recipient, U256::one()), Err(Error::Overflow));
+        }
+
+        #[ink::test]
+        fn transfer_recipient_balance_overflow_fails() {
+            let sender = Address::from([0x01; 20]);
+            let recipient = Address::from([0x02; 20]);
+            set_caller(sender);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            set_balance(&mut token, recipient, U256::MAX - U256::from(10));
+
+            assert!(token.transfer(recipient, U256::from(10)).is_ok());
+            assert_eq!(token.balance_of(recipient), U256::MAX);
+
+            assert_eq!(token.transfer(recipient, U256::one()), Err(Error::Overflow));
+            assert_eq!(token.balance_of(recipient), U256::MAX);
+        }
+
+        #[ink::test]
+        fn renounce_ownership_disables_owner_operations() {
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            assert_eq!(token.owner(), Some(owner));
+
+            assert_eq!(token.renounce_ownership(), Ok(()));
+            assert_eq!(token.owner(), None);
+
+            let recipient = Address::from([0x02; 20]);
+            assert_eq!(
+                token.mint(recipient, U256::from(100)),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(
+                token.mint_batch(vec![(recipient, U256::from(100))]),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(token.snapshot(), Err(Error::NotAuthorized));
+            assert_eq!(
+                token.set_name(String::from("Renamed")),
+                Err(Error::NotAuthorized)
+            );
+            assert_eq!(token.renounce_ownership(), Err(Error::NotAuthorized));
+            assert_eq!(token.total_supply(), U256::from(1000));
+
+            // Plain transfers keep working
+            assert!(token.transfer(recipient, U256::from(100)).is_ok());
+        }
+
+        #[ink::test]
+        fn renounce_ownership_non_owner_fails() {
+            let owner = Address::from([0x01; 20]);
+            set_caller(owner);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+
+            set_caller(Address::from([0x02; 20]));
+            assert_eq!(token.renounce_ownership(), Err(Error::NotAuthorized));
+            assert_eq!(token.owner(), Some(owner));
+        }
+
+        #[ink::test]
+        fn two_step_ownership_transfer_works() {
+            let owner = Address::from([0x01; 20]);
+            let new_owner = Address::from([0x02; 20]);
+            set_caller(owner);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+
+            assert_eq!(token.transfer_ownership(new_owner), Ok(()));
+            assert_eq!(token.pending_owner(), Some(new_owner));
+            // Ownership only moves on acceptance
+            assert_eq!(token.owner(), Some(owner));
+
+            // A different account cannot accept
+            set_caller(Address::from([0x03; 20]));
+            assert_eq!(token.accept_ownership(), Err(Error::NotAuthorized));
+
+            set_caller(new_owner);
+            assert_eq!(token.accept_ownership(), Ok(()));
+            assert_eq!(token.owner(), Some(new_owner));
+            assert_eq!(token.pending_owner(), None);
+            assert!(token.snapshot().is_ok());
+
+            set_caller(owner);
+            assert_eq!(token.snapshot(), Err(Error::NotAuthorized));
+        }
+
+        #[ink::test]
+        fn cancel_ownership_transfer_works() {
+            let owner = Address::from([0x01; 20]);
+            let new_owner = Address::from([0x02; 20]);
+            set_caller(owner);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+
+            assert_eq!(token.transfer_ownership(new_owner), Ok(()));
+            set_caller(new_owner);
+            assert_eq!(token.cancel_ownership_transfer(), Err(Error::NotAuthorized));
+
+            set_caller(owner);
+            assert_eq!(token.cancel_ownership_transfer(), Ok(()));
+            assert_eq!(token.pending_owner(), None);
+
+            set_caller(new_owner);
+            assert_eq!(token.accept_ownership(), Err(Error::NotAuthorized));
+            assert_eq!(token.owner(), Some(owner));
+        }
+
+        #[ink::test]
+        fn approve_until_expires() {
+            let owner = Address::from([0x01; 20]);
+            let spender = Address::from([0x02; 20]);
+            let recipient = Address::from([0x03; 20]);
+
+            set_caller(owner);
+            set_block_timestamp(1000);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                None,
+            );
+            assert_eq!(token.approve_until(spender, U256::from(300), 2000), Ok(()));
+            assert_eq!(token.allowance_expiry(owner, spender), Some(2000));
+
+            // Usable up to and including the expiry
+            set_caller(spender);
+            set_block_timestamp(2000);
+            assert_eq!(
+                token.transfer_from(owner, recipient, U256::from(100)),
+                Ok(())
+            );
+            assert_eq!(token.allowance(owner, spender), U256::from(200));
+
+            set_block_timestamp(2001);
+            assert_eq!(token.allowance(owner, spender), U256::zero());
+            assert_eq!(
+                token.transfer_from(owner, recipient, U256::from(100)),
+                Err(Error::InsufficientAllowance)
+            );
+            assert_eq!(token.balance_of(recipient), U256::from(100));
+
+            // A plain approve grants a non-expiring allowance again
+            set_caller(owner);
+            assert_eq!(token.approve(spender, U256::from(50)), Ok(U256::zero()));
+            assert_eq!(token.allowance_expiry(owner, spender), None);
+            assert_eq!(token.allowance(owner, spender), U256::from(50));
+        }
+
+        #[ink::test]
+        fn approve_until_respects_max_approval() {
+            let owner = Address::from([0x01; 20]);
+            let spender = Address::from([0x02; 20]);
+
+            set_caller(owner);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                U256::from(1000),
+                Some(U256::from(100)),
+            );
+            assert_eq!(
+                token.approve_until(spender, U256::from(101), 2000),
+                Err(Error::ApprovalTooLarge)
+            );
+            assert_eq!(token.allowance(owner, spender), U256::zero());
+            assert_eq!(token.allowance_expiry(owner, spender), None);
+        }
+
+        #[ink::test]
+        fn transfer_whole_scales_by_decimals() {
+            let owner = Address::from([0x01; 20]);
+            let recipient = Address::from([0x02; 20]);
+            let one_token = U256::from(10).pow(U256::from(18));
+
+            set_caller(owner);
+            let mut token = Token::new(
+                String::from("Test Token"),
+                String::from("TEST"),
+                18,
+                one_token * 10,
+                None,
+            );
+
+            assert_eq!(token.transfer_whole(recipient, U256::from(5)), Ok(()));
+            assert_eq!(token.balance_of(recipient), one_token * 5);
+            assert_eq!(token.balance_of(owner), one_token * 5);
+
+            assert_eq!(
+                token.transfer_whole(recipient, U256::from(6)),
+                Err(Error::InsufficientBalance)
+            );
+            assert_eq!(
+                token.transfer_whole(recipient, U256::MAX),
+                Err(Error::Overflow)
+            );
+            assert_eq!(token.balance_of(recipient), one_token * 5);
         }
     }
 }